@@ -8,6 +8,7 @@ $outputFiles = @(
     @{ src = "reports/registries.json"; dst = "registries.json" },
     @{ src = "reports/biome_parameters/minecraft/*"; dst = "biomes" }
 )
+$tagRegistries = @("block", "item", "fluid", "entity_type", "game_event")
 
 # Download server JAR if missing
 if (-not (Test-Path $serverJar)) {
@@ -17,7 +18,7 @@ if (-not (Test-Path $serverJar)) {
 
 # Run the data generator
 Write-Host "Running Minecraft data generator..."
-java -DbundlerMainClass="net.minecraft.data.Main" -jar $serverJar --reports
+java -DbundlerMainClass="net.minecraft.data.Main" -jar $serverJar --reports --server
 
 # Ensure generated folder exists
 if (-not (Test-Path $generatedDir)) {
@@ -29,6 +30,10 @@ if (-not (Test-Path "$generatedDir\biomes")) {
     New-Item -ItemType Directory -Path "$generatedDir\biomes" | Out-Null
 }
 
+if (-not (Test-Path "$generatedDir\tags")) {
+    New-Item -ItemType Directory -Path "$generatedDir\tags" | Out-Null
+}
+
 # Copy the generated files
 foreach ($file in $outputFiles) {
     $src = Join-Path "./generated" $file.src
@@ -39,4 +44,19 @@ foreach ($file in $outputFiles) {
     } else {
         Write-Warning "$($file.dst) not found at $src"
     }
+}
+
+# Copy the vanilla tags, --server puts them in the built-in data pack
+foreach ($registry in $tagRegistries) {
+    $src = "./generated/data/minecraft/tags/$registry"
+    $dst = Join-Path $generatedDir "tags/$registry"
+    if (Test-Path $src) {
+        if (Test-Path $dst) {
+            Remove-Item $dst -Recurse -Force
+        }
+        Copy-Item $src $dst -Recurse -Force
+        Write-Host "Copied $registry tags to $generatedDir"
+    } else {
+        Write-Warning "$registry tags not found at $src"
+    }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
     path::Path,
 };
@@ -19,10 +19,86 @@ fn pascal_case(s: &str) -> String {
         .collect::<String>()
 }
 
+// A property whose values all parse as `value_type`
+fn value_property(name: &str, type_name: &str, value_type: &str) -> String {
+    format!(
+        r#"
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct {type_name}(pub {value_type});
+
+    impl Property for {type_name} {{
+        const NAME: &'static str = "{name}";
+
+        fn parse(value: &str) -> Option<Self> {{
+            value.parse().ok().map({type_name})
+        }}
+    }}
+"#
+    )
+}
+
+// Every tag file under `dir` by its name, a tag in a subfolder is named after its path like minecraft:mineable/axe
+fn read_tags(dir: &Path, prefix: &str, tags: &mut BTreeMap<String, Value>) {
+    for entry in fs::read_dir(dir).expect("Failed to read tags dir") {
+        let path = entry.expect("Invalid entry").path();
+        let name = path.file_stem().unwrap().to_str().unwrap().to_string();
+
+        if path.is_dir() {
+            read_tags(&path, &format!("{}{}/", prefix, name), tags);
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            let contents = fs::read_to_string(&path).expect("Failed to read tag file");
+            let tag: Value = serde_json::from_str(&contents).expect("Invalid tag file");
+            tags.insert(format!("minecraft:{}{}", prefix, name), tag);
+        }
+    }
+}
+
+// The protocol ids in a tag with the tags it references flattened into it, optional entries that don't exist are
+// left out like the game does
+fn resolve_tag(
+    name: &str,
+    tags: &BTreeMap<String, Value>,
+    registry: &Value,
+    resolved: &mut BTreeMap<String, Vec<u32>>,
+) -> Vec<u32> {
+    if let Some(ids) = resolved.get(name) {
+        return ids.clone();
+    }
+
+    let mut ids = Vec::new();
+    for value in tags[name]["values"].as_array().unwrap() {
+        let (entry, required) = match value.as_str() {
+            Some(entry) => (entry, true),
+            None => (value["id"].as_str().unwrap(), value["required"].as_bool().unwrap_or(true)),
+        };
+
+        let found = match entry.strip_prefix('#') {
+            Some(tag) => tags.contains_key(tag).then(|| resolve_tag(tag, tags, registry, resolved)),
+            None => registry[entry]["protocol_id"].as_u64().map(|id| vec![id as u32]),
+        };
+
+        match found {
+            Some(found) => {
+                for id in found {
+                    if !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                }
+            }
+            None if required => panic!("{} refers to {}, which doesn't exist", name, entry),
+            None => {}
+        }
+    }
+
+    resolved.insert(name.to_string(), ids.clone());
+    ids
+}
+
 fn main() {
     let blocks_path = Path::new("./codegen/generator/generated/blocks.json");
     let registries_path = Path::new("./codegen/generator/generated/registries.json");
     let biomes_dir = Path::new("./codegen/generator/generated/biomes");
+    let tags_dir = Path::new("./codegen/generator/generated/tags");
 
     let output_path = Path::new("./rustmine_lib/src/blocks.rs");
 
@@ -39,46 +115,118 @@ fn main() {
     let block_registry = &registries_json["minecraft:block"]["entries"];
 
     let mut enum_variants = String::new();
+    let mut all_blocks = String::new();
     let mut registry_entries = String::new();
+    let mut first_states = Vec::new();
+    let mut names = Vec::new();
+    let mut property_values: BTreeMap<String, Vec<String>> = BTreeMap::new();
 
     for (block_name, block_info) in blocks_json.as_object().unwrap() {
         let enum_name = pascal_case(block_name.strip_prefix("minecraft:").unwrap());
 
         enum_variants.push_str(&format!("    {},\n", enum_name));
+        all_blocks.push_str(&format!("        Block::{},\n", enum_name));
+
+        // Sorted by name like the states are numbered, the last property changes fastest
+        let mut property_entries = String::new();
+        if let Some(properties) = block_info["properties"].as_object() {
+            for (name, values) in properties {
+                let values: Vec<&str> = values.as_array().unwrap().iter().map(|v| v.as_str().unwrap()).collect();
+
+                let known = property_values.entry(name.clone()).or_default();
+                for value in &values {
+                    if !known.iter().any(|v| v == value) {
+                        known.push(value.to_string());
+                    }
+                }
 
-        let states = block_info["states"].as_array().unwrap();
-        let mut state_entries = String::new();
-        let mut default_state = None;
-
-        for state in states {
-            let id = state["id"].as_u64().unwrap();
-            let props = state["properties"]
-                .as_object()
-                .unwrap_or(&serde_json::Map::default())
-                .iter()
-                .map(|(k, v)| format!("(\"{}\", \"{}\")", k, v.as_str().unwrap()))
-                .collect::<Vec<_>>()
-                .join(", ");
-
-            state_entries.push_str(&format!(
-                "        BlockState {{ id: {}, properties: &[{}] }},\n",
-                id, props
-            ));
-
-            if state.get("default").is_some() && state["default"].as_bool().unwrap() {
-                default_state = Some(id);
+                property_entries.push_str(&format!(
+                    "            (\"{}\", &[{}]),\n",
+                    name,
+                    values.iter().map(|v| format!("\"{}\"", v)).join(", ")
+                ));
             }
         }
 
-        let default_id = default_state.unwrap_or(states[0]["id"].as_u64().unwrap());
+        let states = block_info["states"].as_array().unwrap();
+        let first_state = states.iter().map(|state| state["id"].as_u64().unwrap()).min().unwrap();
+        let default_state = states
+            .iter()
+            .find(|state| state["default"].as_bool().unwrap_or(false))
+            .map_or(first_state, |state| state["id"].as_u64().unwrap());
         let protocol_id = block_registry[block_name]["protocol_id"].as_u64().unwrap();
 
+        first_states.push((first_state, enum_name.clone()));
+        names.push((block_name.clone(), enum_name.clone()));
+
+        let properties = if property_entries.is_empty() {
+            "&[]".to_string()
+        } else {
+            format!("&[\n{}        ]", property_entries)
+        };
+
         registry_entries.push_str(&format!(
-            "    Block::{0} => BlockRegistryEntry {{\n        name: \"{1}\",\n        states: &[\n{2}        ],\n        default_state: {3},\n        protocol_id: {4},\n    }},\n",
-            enum_name, block_name, state_entries, default_id, protocol_id
+            "    Block::{0} => BlockRegistryEntry {{\n        name: \"{1}\",\n        properties: {2},\n        first_state: {3},\n        default_state: {4},\n        protocol_id: {5},\n    }},\n",
+            enum_name, block_name, properties, first_state, default_state, protocol_id
         ));
     }
 
+    let state_count = blocks_json
+        .as_object()
+        .unwrap()
+        .values()
+        .map(|block_info| block_info["states"].as_array().unwrap().len())
+        .sum::<usize>();
+
+    let blocks_by_state = first_states
+        .iter()
+        .sorted()
+        .map(|(first_state, enum_name)| format!("    ({}, Block::{}),\n", first_state, enum_name))
+        .collect::<String>();
+
+    // Sorted here rather than relying on the order of blocks.json, Block::from_name binary searches it
+    let blocks_by_name = names
+        .iter()
+        .sorted()
+        .map(|(name, enum_name)| format!("    (\"{}\", Block::{}),\n", name, enum_name))
+        .collect::<String>();
+
+    // One type per property name, over every value any block gives it
+    let mut property_types = String::new();
+    for (name, values) in &property_values {
+        let type_name = pascal_case(name);
+
+        if values.iter().all(|v| v == "true" || v == "false") {
+            property_types.push_str(&value_property(name, &type_name, "bool"));
+        } else if values.iter().all(|v| v.parse::<u8>().is_ok()) {
+            property_types.push_str(&value_property(name, &type_name, "u8"));
+        } else {
+            let variants = values.iter().map(|v| format!("        {},\n", pascal_case(v))).collect::<String>();
+            let arms = values
+                .iter()
+                .map(|v| format!("                \"{}\" => Some({}::{}),\n", v, type_name, pascal_case(v)))
+                .collect::<String>();
+
+            property_types.push_str(&format!(
+                r#"
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum {type_name} {{
+{variants}    }}
+
+    impl Property for {type_name} {{
+        const NAME: &'static str = "{name}";
+
+        fn parse(value: &str) -> Option<Self> {{
+            match value {{
+{arms}                _ => None,
+            }}
+        }}
+    }}
+"#
+            ));
+        }
+    }
+
     let generated = format!(
         r#"
 // AUTO-GENERATED FILE. DO NOT EDIT.
@@ -87,25 +235,45 @@ fn main() {
 pub enum Block {{
 {enum_variants}}}
 
-pub struct BlockState {{
-    pub id: u32,
-    pub properties: &'static [(&'static str, &'static str)],
+impl Block {{
+    pub const ALL: &'static [Block] = &[
+{all_blocks}    ];
 }}
 
 pub struct BlockRegistryEntry {{
     pub name: &'static str,
-    pub states: &'static [BlockState],
-    pub default_state: u64,
+    pub properties: &'static [(&'static str, &'static [&'static str])],
+    pub first_state: u32,
+    pub default_state: u32,
     pub protocol_id: u64,
 }}
 
+pub const BLOCK_STATE_COUNT: u32 = {state_count};
+
+// The first state of every block, in order
+pub(crate) const BLOCKS_BY_STATE: &[(u32, Block)] = &[
+{blocks_by_state}];
+
+// Every block by its name, sorted by name
+pub(crate) const BLOCKS_BY_NAME: &[(&str, Block)] = &[
+{blocks_by_name}];
+
 pub fn get_block_registry_entry(block: Block) -> BlockRegistryEntry {{
     match block {{
 {registry_entries}    }}
 }}
+
+pub mod properties {{
+    use crate::block_state::Property;
+{property_types}}}
 "#,
         enum_variants = enum_variants,
+        all_blocks = all_blocks,
+        state_count = state_count,
+        blocks_by_state = blocks_by_state,
+        blocks_by_name = blocks_by_name,
         registry_entries = registry_entries,
+        property_types = property_types,
     );
 
     fs::write(output_path, generated).unwrap();
@@ -130,20 +298,14 @@ pub fn get_block_registry_entry(block: Block) -> BlockRegistryEntry {{
                     let enum_name = pascal_case(name.strip_prefix("minecraft:").unwrap());
                     let p = &biome_entry["parameters"];
 
-                    let format_range = |v: &Value| -> String {
-                        if let Some(arr) = v.as_array() {
-                            format!("[{}, {}]", arr[0], arr[1])
-                        } else {
-                            "[0.0, 0.0]".to_string()
-                        }
-                    };
-
+                    // Debug keeps the decimal point on whole numbers, the fields are floats.
+                    // A single value (the nether preset has nothing else) is a range from and to it.
                     let parse_range_or_scalar = |v: &Value| -> String {
                         if let Some(arr) = v.as_array() {
-                            format!("[{}, {}]", arr[0], arr[1])
+                            format!("[{:?}, {:?}]", arr[0].as_f64().unwrap(), arr[1].as_f64().unwrap())
                         } else {
                             let val = v.as_f64().unwrap_or(0.0);
-                            format!("[{}, {}]", val, val)
+                            format!("[{:?}, {:?}]", val, val)
                         }
                     };
 
@@ -151,11 +313,11 @@ pub fn get_block_registry_entry(block: Block) -> BlockRegistryEntry {{
                         "        BiomeParameters {{ depth: {}, offset: {}, temperature: {}, humidity: {}, continentalness: {}, erosion: {}, weirdness: {} }},\n",
                         parse_range_or_scalar(&p["depth"]),
                         parse_range_or_scalar(&p["offset"]),
-                        format_range(&p["temperature"]),
-                        format_range(&p["humidity"]),
-                        format_range(&p["continentalness"]),
-                        format_range(&p["erosion"]),
-                        format_range(&p["weirdness"]),
+                        parse_range_or_scalar(&p["temperature"]),
+                        parse_range_or_scalar(&p["humidity"]),
+                        parse_range_or_scalar(&p["continentalness"]),
+                        parse_range_or_scalar(&p["erosion"]),
+                        parse_range_or_scalar(&p["weirdness"]),
                     );
 
                     biome_param_map
@@ -168,12 +330,19 @@ pub fn get_block_registry_entry(block: Block) -> BlockRegistryEntry {{
     }
 
     let mut biome_enum_str = String::new();
+    let mut biome_all_str = String::new();
+    let mut biome_identifier_match_arms = String::new();
     let mut biome_lookup_match_arms = String::new();
 
     for biome in biome_enum_variants.iter().sorted() {
         let enum_name = pascal_case(biome.strip_prefix("minecraft:").unwrap());
 
         biome_enum_str.push_str(&format!("    {},\n", enum_name));
+        biome_all_str.push_str(&format!("        Biome::{},\n", enum_name));
+        biome_identifier_match_arms.push_str(&format!(
+            "            Biome::{} => \"{}\",\n",
+            enum_name, biome
+        ));
 
         let all_params = biome_param_map.get(&enum_name).unwrap_or(&vec![]).join("");
         biome_lookup_match_arms.push_str(&format!(
@@ -190,6 +359,16 @@ pub fn get_block_registry_entry(block: Block) -> BlockRegistryEntry {{
 pub enum Biome {{
 {biome_enum_str}}}
 
+impl Biome {{
+    pub const ALL: &'static [Biome] = &[
+{biome_all_str}    ];
+
+    pub fn identifier(&self) -> &'static str {{
+        match self {{
+{biome_identifier_match_arms}        }}
+    }}
+}}
+
 #[derive(Debug)]
 pub struct BiomeParameters {{
     pub depth: [f32; 2],
@@ -207,9 +386,52 @@ pub fn get_biome_parameters(biome: Biome) -> &'static [BiomeParameters] {{
 }}
 "#,
         biome_enum_str = biome_enum_str,
+        biome_all_str = biome_all_str,
+        biome_identifier_match_arms = biome_identifier_match_arms,
         biome_lookup_match_arms = biome_lookup_match_arms
     );
 
     fs::write(output_path, generated).unwrap();
     println!("Generated: {}", output_path.display());
+
+    // --- TAGS ---
+    let output_path = Path::new("./rustmine_lib/src/vanilla_tags.rs");
+    let mut registry_tables = String::new();
+
+    for registry in ["block", "entity_type", "fluid", "game_event", "item"] {
+        let registry_name = format!("minecraft:{}", registry);
+        let entries = &registries_json[&registry_name]["entries"];
+
+        let mut tags = BTreeMap::new();
+        read_tags(&tags_dir.join(registry), "", &mut tags);
+
+        let mut resolved = BTreeMap::new();
+        for name in tags.keys() {
+            resolve_tag(name, &tags, entries, &mut resolved);
+        }
+
+        let tag_entries = resolved
+            .iter()
+            .map(|(name, ids)| format!("        (\"{}\", &[{}]),\n", name, ids.iter().join(", ")))
+            .collect::<String>();
+
+        registry_tables.push_str(&format!("    (\"{}\", &[\n{}    ]),\n", registry_name, tag_entries));
+    }
+
+    let generated = format!(
+        r#"
+// AUTO-GENERATED FILE. DO NOT EDIT.
+
+// A tag's name and the protocol ids in it, tags it referenced are already flattened into it
+pub type VanillaTag = (&'static str, &'static [u32]);
+
+// Every vanilla tag by registry
+pub const VANILLA_TAGS: &[(&str, &[VanillaTag])] = &[
+{registry_tables}];
+"#,
+        registry_tables = registry_tables,
+    );
+
+    fs::write(output_path, generated).unwrap();
+    println!("Generated: {}", output_path.display());
 }
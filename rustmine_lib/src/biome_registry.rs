@@ -0,0 +1,116 @@
+use std::io::{Error, ErrorKind};
+
+use crate::biomes::Biome;
+
+/// The biomes synced to clients as minecraft:worldgen/biome. Chunk data refers to a biome by its position in here.
+#[derive(Clone, Debug)]
+pub struct BiomeRegistry {
+    biomes: Vec<Biome>,
+}
+
+impl Default for BiomeRegistry {
+    fn default() -> Self {
+        Self::new(Biome::ALL.to_vec())
+    }
+}
+
+impl BiomeRegistry {
+    /// Duplicates keep their first position.
+    pub fn new(biomes: Vec<Biome>) -> Self {
+        let mut unique = Vec::with_capacity(biomes.len());
+        for biome in biomes {
+            if !unique.contains(&biome) {
+                unique.push(biome);
+            }
+        }
+        Self { biomes: unique }
+    }
+
+    /// Every biome, in the order they're synced.
+    pub fn biomes(&self) -> &[Biome] {
+        &self.biomes
+    }
+
+    pub fn len(&self) -> usize {
+        self.biomes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.biomes.is_empty()
+    }
+
+    pub fn id(&self, biome: Biome) -> Option<u32> {
+        self.biomes.iter().position(|entry| *entry == biome).map(|id| id as u32)
+    }
+
+    pub fn get(&self, id: u32) -> Option<Biome> {
+        self.biomes.get(id as usize).copied()
+    }
+
+    /// Like `id`, but a biome the client wasn't sent is an error.
+    pub fn network_id(&self, biome: Biome) -> Result<u32, Box<Error>> {
+        self.id(biome).ok_or_else(|| {
+            Box::new(Error::new(
+                ErrorKind::NotFound,
+                format!("{} isn't in the synced biome registry", biome.identifier()),
+            ))
+        })
+    }
+
+    /// Bits per entry of a biome container too varied for a palette, what the client expects for this many biomes.
+    pub fn global_bits(&self) -> u8 {
+        bits_for(self.biomes.len())
+    }
+}
+
+// Enough bits to tell `count` values apart
+pub(crate) fn bits_for(count: usize) -> u8 {
+    count.max(1).next_power_of_two().trailing_zeros() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_first_of_duplicates() {
+        let registry = BiomeRegistry::new(vec![Biome::Desert, Biome::Plains, Biome::Desert, Biome::Forest]);
+
+        assert_eq!(registry.biomes(), [Biome::Desert, Biome::Plains, Biome::Forest]);
+        assert_eq!(registry.id(Biome::Desert), Some(0));
+        assert_eq!(registry.id(Biome::Forest), Some(2));
+    }
+
+    #[test]
+    fn maps_ids_both_ways() {
+        let registry = BiomeRegistry::default();
+        assert_eq!(registry.len(), Biome::ALL.len());
+
+        for (id, biome) in Biome::ALL.iter().enumerate() {
+            assert_eq!(registry.id(*biome), Some(id as u32));
+            assert_eq!(registry.get(id as u32), Some(*biome));
+        }
+        assert_eq!(registry.get(Biome::ALL.len() as u32), None);
+    }
+
+    #[test]
+    fn rejects_biomes_that_are_not_synced() {
+        let registry = BiomeRegistry::new(vec![Biome::Plains]);
+
+        assert_eq!(registry.network_id(Biome::Plains).unwrap(), 0);
+        let error = registry.network_id(Biome::Desert).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+        assert!(error.to_string().contains("minecraft:desert"));
+    }
+
+    #[test]
+    fn counts_the_bits_for_each_size() {
+        for (count, bits) in [(0, 0), (1, 0), (2, 1), (3, 2), (4, 2), (5, 3), (64, 6), (65, 7)] {
+            assert_eq!(bits_for(count), bits, "{}", count);
+        }
+
+        assert_eq!(BiomeRegistry::new(vec![Biome::Plains]).global_bits(), 0);
+        assert_eq!(BiomeRegistry::new(vec![Biome::Plains, Biome::Desert, Biome::Forest]).global_bits(), 2);
+        assert_eq!(BiomeRegistry::default().global_bits(), bits_for(Biome::ALL.len()));
+    }
+}
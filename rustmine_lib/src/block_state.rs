@@ -0,0 +1,323 @@
+use std::{
+    fmt,
+    io::{Error, ErrorKind},
+    str::FromStr,
+};
+
+use crate::blocks::{
+    get_block_registry_entry, Block, BlockRegistryEntry, BLOCKS_BY_NAME, BLOCKS_BY_STATE, BLOCK_STATE_COUNT,
+};
+
+//
+// Every combination of a block's properties is a state with its own id, which is what chunks store and the protocol
+// sends. A block's states are numbered from its first state with the properties sorted by name and the last one
+// changing fastest, so the properties of a state are worked out from its id and back without a table of every state.
+//
+
+/// A typed block property, one is generated for every property name in `blocks::properties`.
+pub trait Property: Copy + PartialEq + fmt::Debug {
+    const NAME: &'static str;
+
+    fn parse(value: &str) -> Option<Self>;
+}
+
+/// A block state, by its global id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockState(u32);
+
+impl BlockState {
+    pub const AIR: BlockState = BlockState(0);
+
+    pub fn from_id(id: u32) -> Option<Self> {
+        (id < BLOCK_STATE_COUNT).then_some(BlockState(id))
+    }
+
+    pub fn id(self) -> u32 {
+        self.0
+    }
+
+    pub fn block(self) -> Block {
+        let index = BLOCKS_BY_STATE.partition_point(|(first_state, _)| *first_state <= self.0);
+        BLOCKS_BY_STATE[index - 1].1
+    }
+
+    pub fn is_default(self) -> bool {
+        get_block_registry_entry(self.block()).default_state == self.0
+    }
+
+    /// Air, cave air and void air.
+    pub fn is_air(self) -> bool {
+        matches!(self.block(), Block::Air | Block::CaveAir | Block::VoidAir)
+    }
+
+    /// Every property of the state and its value, sorted by name.
+    pub fn properties(self) -> Vec<(&'static str, &'static str)> {
+        let entry = get_block_registry_entry(self.block());
+        let mut offset = self.0 - entry.first_state;
+
+        let mut properties: Vec<_> = entry
+            .properties
+            .iter()
+            .rev()
+            .map(|(name, values)| {
+                let value = values[offset as usize % values.len()];
+                offset /= values.len() as u32;
+                (*name, value)
+            })
+            .collect();
+        properties.reverse();
+        properties
+    }
+
+    pub fn property(self, name: &str) -> Option<&'static str> {
+        self.properties()
+            .into_iter()
+            .find(|(property, _)| *property == name)
+            .map(|(_, value)| value)
+    }
+
+    /// The value of `P`, None if the block doesn't have it.
+    pub fn get<P: Property>(self) -> Option<P> {
+        self.property(P::NAME).and_then(P::parse)
+    }
+
+    /// The same block with `value` set, None if the block doesn't have the property or can't take that value.
+    pub fn try_with<P: Property>(self, value: P) -> Option<Self> {
+        let entry = get_block_registry_entry(self.block());
+        let (_, values) = entry.properties.iter().find(|(name, _)| *name == P::NAME)?;
+        let value = values.iter().find(|v| P::parse(v) == Some(value))?;
+        self.with_property(P::NAME, value)
+    }
+
+    /// Like `try_with`, but panics when the block can't take `value`.
+    pub fn with<P: Property>(self, value: P) -> Self {
+        self.try_with(value).unwrap_or_else(|| {
+            panic!("{} can't have {} set to {:?}", self.block().name(), P::NAME, value)
+        })
+    }
+
+    /// The same block with the property called `name` set to `value`, None if it can't take it.
+    pub fn with_property(self, name: &str, value: &str) -> Option<Self> {
+        let entry = get_block_registry_entry(self.block());
+        let index = entry.properties.iter().position(|(property, _)| *property == name)?;
+        let (_, values) = entry.properties[index];
+        let new = values.iter().position(|v| *v == value)? as u32;
+
+        let stride = stride(&entry, index);
+        let current = (self.0 - entry.first_state) / stride % values.len() as u32;
+        Some(BlockState(self.0 - current * stride + new * stride))
+    }
+}
+
+// How far apart the states are for consecutive values of the property at `index`
+fn stride(entry: &BlockRegistryEntry, index: usize) -> u32 {
+    entry.properties[index + 1..]
+        .iter()
+        .map(|(_, values)| values.len() as u32)
+        .product()
+}
+
+impl Default for BlockState {
+    fn default() -> Self {
+        BlockState::AIR
+    }
+}
+
+impl From<Block> for BlockState {
+    fn from(block: Block) -> Self {
+        block.default_state()
+    }
+}
+
+// Formatted like commands take them, minecraft:oak_stairs[facing=north,half=top,shape=straight,waterlogged=false]
+impl fmt::Display for BlockState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.block().name())?;
+
+        let properties = self.properties();
+        if !properties.is_empty() {
+            let properties: Vec<String> = properties
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            write!(f, "[{}]", properties.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+// The namespace can be left out and so can any property, those keep their default value
+impl FromStr for BlockState {
+    type Err = Box<Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |message: String| Box::new(Error::new(ErrorKind::InvalidInput, message));
+
+        let (name, properties) = match s.split_once('[') {
+            Some((name, properties)) => {
+                let properties = properties
+                    .strip_suffix(']')
+                    .ok_or_else(|| invalid(format!("Missing ] in block state {}", s)))?;
+                (name, properties)
+            }
+            None => (s, ""),
+        };
+
+        let block = if name.contains(':') {
+            Block::from_name(name)
+        } else {
+            Block::from_name(&format!("minecraft:{}", name))
+        }
+        .ok_or_else(|| invalid(format!("Unknown block {}", name)))?;
+
+        let properties = properties
+            .split(',')
+            .filter(|property| !property.is_empty())
+            .map(|property| {
+                property
+                    .split_once('=')
+                    .ok_or_else(|| invalid(format!("Expected name=value, got {} in block state {}", property, s)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        block.state(&properties)
+    }
+}
+
+impl Block {
+    pub fn name(self) -> &'static str {
+        get_block_registry_entry(self).name
+    }
+
+    /// The block called `name`, namespace included.
+    pub fn from_name(name: &str) -> Option<Block> {
+        BLOCKS_BY_NAME
+            .binary_search_by(|(block_name, _)| (*block_name).cmp(name))
+            .ok()
+            .map(|index| BLOCKS_BY_NAME[index].1)
+    }
+
+    pub fn default_state(self) -> BlockState {
+        BlockState(get_block_registry_entry(self).default_state)
+    }
+
+    /// Every state of the block, in id order.
+    pub fn states(self) -> impl Iterator<Item = BlockState> {
+        let entry = get_block_registry_entry(self);
+        let count: u32 = entry.properties.iter().map(|(_, values)| values.len() as u32).product();
+        (entry.first_state..entry.first_state + count).map(BlockState)
+    }
+
+    /// The state with the given properties, the ones left out keep their default value.
+    pub fn state(self, properties: &[(&str, &str)]) -> Result<BlockState, Box<Error>> {
+        let mut state = self.default_state();
+
+        for (index, (name, value)) in properties.iter().enumerate() {
+            if properties[..index].iter().any(|(other, _)| other == name) {
+                return Err(Box::new(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Property {} of {} is set twice", name, self.name()),
+                )));
+            }
+
+            state = state.with_property(name, value).ok_or_else(|| {
+                Box::new(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{} can't have {} set to {}", self.name(), name, value),
+                ))
+            })?;
+        }
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::properties::{Facing, Half, Waterlogged};
+
+    #[test]
+    fn finds_every_block_by_name() {
+        assert!(BLOCKS_BY_NAME.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(BLOCKS_BY_NAME.len(), Block::ALL.len());
+
+        for block in Block::ALL {
+            assert_eq!(Block::from_name(block.name()), Some(*block));
+        }
+        assert_eq!(Block::from_name("oak_stairs"), None);
+        assert_eq!(Block::from_name("minecraft:not_a_block"), None);
+    }
+
+    #[test]
+    fn round_trips_every_state_through_its_properties() {
+        for id in 0..BLOCK_STATE_COUNT {
+            let state = BlockState::from_id(id).unwrap();
+            assert_eq!(state.block().state(&state.properties()).unwrap(), state, "state {}", id);
+            assert_eq!(state.to_string().parse::<BlockState>().unwrap(), state, "state {}", id);
+        }
+        assert_eq!(BlockState::from_id(BLOCK_STATE_COUNT), None);
+    }
+
+    #[test]
+    fn numbers_states_with_the_last_property_fastest() {
+        let stairs: Vec<BlockState> = Block::OakStairs.states().collect();
+        assert_eq!(stairs.len(), 4 * 2 * 5 * 2);
+        assert_eq!(stairs[0].id(), 2938);
+        assert!(stairs.iter().all(|state| state.block() == Block::OakStairs));
+
+        assert_eq!(stairs[0].properties(), [
+            ("facing", "north"),
+            ("half", "top"),
+            ("shape", "straight"),
+            ("waterlogged", "true"),
+        ]);
+        assert_eq!(stairs[1].property("waterlogged"), Some("false"));
+        assert_ne!(BlockState::from_id(2937).unwrap().block(), Block::OakStairs);
+        assert_ne!(BlockState::from_id(2938 + 80).unwrap().block(), Block::OakStairs);
+    }
+
+    #[test]
+    fn parses_and_formats_states() {
+        let state: BlockState = "minecraft:oak_stairs[facing=north,half=top]".parse().unwrap();
+        assert_eq!(state.id(), 2939);
+        assert_eq!(
+            state.to_string(),
+            "minecraft:oak_stairs[facing=north,half=top,shape=straight,waterlogged=false]"
+        );
+
+        // The namespace and the properties can be left out, in any order
+        assert_eq!("oak_stairs".parse::<BlockState>().unwrap(), Block::OakStairs.default_state());
+        assert_eq!("oak_stairs[half=top,facing=north]".parse::<BlockState>().unwrap(), state);
+        assert_eq!("minecraft:stone".parse::<BlockState>().unwrap().to_string(), "minecraft:stone");
+
+        for invalid in [
+            "minecraft:not_a_block",
+            "oak_stairs[facing=north",
+            "oak_stairs[facing]",
+            "oak_stairs[facing=up]",
+            "oak_stairs[color=red]",
+            "oak_stairs[facing=north,facing=south]",
+        ] {
+            let error = invalid.parse::<BlockState>().unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInput, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn sets_typed_properties() {
+        let state = Block::OakStairs
+            .default_state()
+            .with(Facing::East)
+            .with(Half::Top)
+            .with(Waterlogged(true));
+
+        assert_eq!(state.get::<Facing>(), Some(Facing::East));
+        assert_eq!(state.get::<Half>(), Some(Half::Top));
+        assert_eq!(state.get::<Waterlogged>(), Some(Waterlogged(true)));
+        assert_eq!(Block::Stone.default_state().get::<Facing>(), None);
+        assert_eq!(Block::Stone.default_state().try_with(Facing::East), None);
+        assert!(!state.is_default());
+        assert!(Block::OakStairs.default_state().is_default());
+    }
+}
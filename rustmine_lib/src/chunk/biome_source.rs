@@ -0,0 +1,360 @@
+use crate::biomes::{get_biome_parameters, Biome, BiomeParameters};
+
+use super::{Chunk, BIOME_WIDTH, CHUNK_WIDTH};
+
+const CHILDREN_PER_NODE: usize = 6;
+const DIMENSIONS: usize = 7; // The six climate parameters, then the offset
+
+pub const NETHER_BIOMES: &[Biome] = &[
+    Biome::NetherWastes,
+    Biome::SoulSandValley,
+    Biome::CrimsonForest,
+    Biome::WarpedForest,
+    Biome::BasaltDeltas,
+];
+
+//
+// The multi-noise biome source picks the biome whose parameters are closest to the climate sampled at a position.
+// Every biome covers a box in parameter space (a few boxes, usually), and the distance to a box is zero along the
+// parameters the climate falls within. Boxes are kept in an R-tree, so only the branches that could hold something
+// closer than the best biome so far get looked at.
+//
+
+/// The climate at a position, what the noise router of a generator samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ClimateSample {
+    pub temperature: f32,
+    pub humidity: f32,
+    pub continentalness: f32,
+    pub erosion: f32,
+    pub depth: f32,
+    pub weirdness: f32,
+}
+
+impl ClimateSample {
+    // The offset of the target is always 0, a biome's offset just adds to its distance
+    fn target(&self) -> [f32; DIMENSIONS] {
+        [self.temperature, self.humidity, self.continentalness, self.erosion, self.depth, self.weirdness, 0.0]
+    }
+}
+
+pub trait ClimateSampler: Send + Sync {
+    /// The climate of a quart, in quart coordinates (block coordinates divided by 4).
+    fn sample(&self, x: i32, y: i32, z: i32) -> ClimateSample;
+}
+
+impl<F: Fn(i32, i32, i32) -> ClimateSample + Send + Sync> ClimateSampler for F {
+    fn sample(&self, x: i32, y: i32, z: i32) -> ClimateSample {
+        self(x, y, z)
+    }
+}
+
+pub struct MultiNoiseBiomeSource {
+    tree: Node,
+}
+
+impl MultiNoiseBiomeSource {
+    /// Picks between `biomes`, using every parameter point generated for them. None if none of them have any.
+    pub fn new(biomes: &[Biome]) -> Option<Self> {
+        let leaves: Vec<Node> = biomes
+            .iter()
+            .flat_map(|biome| {
+                get_biome_parameters(*biome).iter().map(|parameters| Node::Leaf {
+                    bounds: Bounds::from(parameters),
+                    biome: *biome,
+                })
+            })
+            .collect();
+
+        if leaves.is_empty() {
+            return None;
+        }
+        Some(Self { tree: Node::build(leaves) })
+    }
+
+    /// Every biome but the nether ones.
+    pub fn overworld() -> Self {
+        let biomes: Vec<Biome> = Biome::ALL
+            .iter()
+            .copied()
+            .filter(|biome| !NETHER_BIOMES.contains(biome))
+            .collect();
+        Self::new(&biomes).expect("The overworld biomes have parameters")
+    }
+
+    pub fn nether() -> Self {
+        Self::new(NETHER_BIOMES).expect("The nether biomes have parameters")
+    }
+
+    /// The biome closest to `climate`.
+    pub fn biome(&self, climate: &ClimateSample) -> Biome {
+        let mut nearest = None;
+        self.tree.nearest(&climate.target(), &mut nearest);
+        nearest.map(|(_, biome)| biome).expect("The tree has at least one leaf")
+    }
+
+    /// Sets the biome of every quart of `chunk` from the climate `sampler` gives, for ChunkGenerator::generate_biomes.
+    pub fn fill_biomes(&self, chunk: &Chunk, sampler: &dyn ClimateSampler) {
+        let quarts = (CHUNK_WIDTH / BIOME_WIDTH) as u8;
+        let (chunk_x, chunk_z) = chunk.coordinates();
+
+        for y in chunk.min_y() >> 2..chunk.max_y() >> 2 {
+            for z in 0..quarts {
+                for x in 0..quarts {
+                    let world_x = chunk_x * quarts as i32 + x as i32;
+                    let world_z = chunk_z * quarts as i32 + z as i32;
+                    let climate = sampler.sample(world_x, y, world_z);
+                    let _ = chunk.set_biome(x, y, z, self.biome(&climate));
+                }
+            }
+        }
+    }
+}
+
+// A box in parameter space, min and max along each dimension
+#[derive(Clone, Copy, Debug)]
+struct Bounds([[f32; 2]; DIMENSIONS]);
+
+impl From<&BiomeParameters> for Bounds {
+    fn from(parameters: &BiomeParameters) -> Self {
+        Bounds([
+            parameters.temperature,
+            parameters.humidity,
+            parameters.continentalness,
+            parameters.erosion,
+            parameters.depth,
+            parameters.weirdness,
+            parameters.offset,
+        ])
+    }
+}
+
+impl Bounds {
+    fn union(&self, other: &Bounds) -> Bounds {
+        let mut union = self.0;
+        for (range, other) in union.iter_mut().zip(other.0) {
+            *range = [range[0].min(other[0]), range[1].max(other[1])];
+        }
+        Bounds(union)
+    }
+
+    // Squared distance from `target` to the closest point of the box
+    fn distance(&self, target: &[f32; DIMENSIONS]) -> f32 {
+        self.0
+            .iter()
+            .zip(target)
+            .map(|([min, max], value)| {
+                let distance = (min - value).max(value - max).max(0.0);
+                distance * distance
+            })
+            .sum()
+    }
+
+    fn center(&self, dimension: usize) -> f32 {
+        (self.0[dimension][0] + self.0[dimension][1]) / 2.0
+    }
+
+    fn size(&self) -> f32 {
+        self.0.iter().map(|[min, max]| max - min).sum()
+    }
+}
+
+enum Node {
+    Leaf { bounds: Bounds, biome: Biome },
+    Branch { bounds: Bounds, children: Vec<Node> },
+}
+
+impl Node {
+    fn bounds(&self) -> &Bounds {
+        match self {
+            Node::Leaf { bounds, .. } | Node::Branch { bounds, .. } => bounds,
+        }
+    }
+
+    fn branch(children: Vec<Node>) -> Node {
+        let bounds = children
+            .iter()
+            .skip(1)
+            .fold(*children[0].bounds(), |bounds, child| bounds.union(child.bounds()));
+        Node::Branch { bounds, children }
+    }
+
+    // Groups the nodes along whichever dimension packs them into the smallest boxes, then groups those, until
+    // there's a single root
+    fn build(mut nodes: Vec<Node>) -> Node {
+        if nodes.len() == 1 {
+            return nodes.pop().unwrap();
+        }
+        if nodes.len() <= CHILDREN_PER_NODE {
+            return Node::branch(nodes);
+        }
+
+        let mut group_size = 1;
+        while group_size * CHILDREN_PER_NODE < nodes.len() {
+            group_size *= CHILDREN_PER_NODE;
+        }
+
+        let cost = |nodes: &[Node]| -> f32 {
+            nodes
+                .chunks(group_size)
+                .map(|group| {
+                    group
+                        .iter()
+                        .skip(1)
+                        .fold(*group[0].bounds(), |bounds, node| bounds.union(node.bounds()))
+                        .size()
+                })
+                .sum()
+        };
+
+        let mut best = (f32::INFINITY, 0);
+        for dimension in 0..DIMENSIONS {
+            nodes.sort_by(|a, b| a.bounds().center(dimension).total_cmp(&b.bounds().center(dimension)));
+            let dimension_cost = cost(&nodes);
+            if dimension_cost < best.0 {
+                best = (dimension_cost, dimension);
+            }
+        }
+        nodes.sort_by(|a, b| a.bounds().center(best.1).total_cmp(&b.bounds().center(best.1)));
+
+        let mut groups = Vec::new();
+        while !nodes.is_empty() {
+            let rest = nodes.split_off(group_size.min(nodes.len()));
+            groups.push(Node::build(nodes));
+            nodes = rest;
+        }
+        Node::branch(groups)
+    }
+
+    // A branch can't hold anything closer than its box, so it's skipped when the box is already further away
+    fn nearest(&self, target: &[f32; DIMENSIONS], nearest: &mut Option<(f32, Biome)>) {
+        let distance = self.bounds().distance(target);
+        if nearest.is_some_and(|(best, _)| distance >= best) {
+            return;
+        }
+
+        match self {
+            Node::Leaf { biome, .. } => *nearest = Some((distance, *biome)),
+            Node::Branch { children, .. } => {
+                // Closest first, so more of the others get skipped
+                let mut children: Vec<&Node> = children.iter().collect();
+                children.sort_by(|a, b| a.bounds().distance(target).total_cmp(&b.bounds().distance(target)));
+                for child in children {
+                    child.nearest(target, nearest);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Squared distance to the closest parameter point of `biome`, worked out without the tree
+    fn distance(biome: Biome, climate: &ClimateSample) -> f32 {
+        let target = [
+            climate.temperature,
+            climate.humidity,
+            climate.continentalness,
+            climate.erosion,
+            climate.depth,
+            climate.weirdness,
+        ];
+
+        get_biome_parameters(biome)
+            .iter()
+            .map(|parameters| {
+                let ranges = [
+                    parameters.temperature,
+                    parameters.humidity,
+                    parameters.continentalness,
+                    parameters.erosion,
+                    parameters.depth,
+                    parameters.weirdness,
+                ];
+                let climate: f32 = ranges
+                    .iter()
+                    .zip(target)
+                    .map(|([min, max], value)| (min - value).max(value - max).max(0.0).powi(2))
+                    .sum();
+                climate + parameters.offset[0].powi(2)
+            })
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    // Climates spread a bit past -1..1, from a fixed seed
+    fn samples(count: usize) -> Vec<ClimateSample> {
+        let mut seed = 12345u64;
+        let mut next = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 40) as f32 / (1 << 24) as f32 * 2.4 - 1.2
+        };
+
+        (0..count)
+            .map(|_| ClimateSample {
+                temperature: next(),
+                humidity: next(),
+                continentalness: next(),
+                erosion: next(),
+                depth: next().abs() * 0.6,
+                weirdness: next(),
+            })
+            .collect()
+    }
+
+    // Ties can go either way, so it's the distance that has to match
+    fn assert_nearest(source: &MultiNoiseBiomeSource, biomes: &[Biome], climate: &ClimateSample) {
+        let nearest = biomes.iter().map(|biome| distance(*biome, climate)).fold(f32::INFINITY, f32::min);
+        let picked = source.biome(climate);
+
+        assert!(biomes.contains(&picked));
+        assert!(
+            (distance(picked, climate) - nearest).abs() < 1e-6,
+            "Picked {:?} for {:?}, something is closer",
+            picked,
+            climate,
+        );
+    }
+
+    #[test]
+    fn picks_the_nearest_overworld_biome() {
+        let source = MultiNoiseBiomeSource::overworld();
+        let biomes: Vec<Biome> = Biome::ALL.iter().copied().filter(|biome| !NETHER_BIOMES.contains(biome)).collect();
+
+        for climate in samples(1000) {
+            assert_nearest(&source, &biomes, &climate);
+        }
+    }
+
+    #[test]
+    fn picks_the_nearest_nether_biome() {
+        let source = MultiNoiseBiomeSource::nether();
+
+        for climate in samples(2000) {
+            assert_nearest(&source, NETHER_BIOMES, &climate);
+        }
+    }
+
+    #[test]
+    fn spreads_the_nether_over_temperature_and_humidity() {
+        let source = MultiNoiseBiomeSource::nether();
+        let biome = |temperature, humidity| {
+            source.biome(&ClimateSample { temperature, humidity, ..Default::default() })
+        };
+
+        assert_eq!(biome(0.0, 0.0), Biome::NetherWastes);
+        assert_eq!(biome(0.0, -0.5), Biome::SoulSandValley);
+        assert_eq!(biome(0.4, 0.0), Biome::CrimsonForest);
+        assert_eq!(biome(0.0, 0.5), Biome::WarpedForest);
+        assert_eq!(biome(-0.5, 0.0), Biome::BasaltDeltas);
+    }
+
+    #[test]
+    fn needs_at_least_one_parameter_point() {
+        assert!(MultiNoiseBiomeSource::new(&[]).is_none());
+
+        let source = MultiNoiseBiomeSource::new(&[Biome::Desert]).unwrap();
+        assert_eq!(source.biome(&ClimateSample::default()), Biome::Desert);
+    }
+}
@@ -1,12 +1,422 @@
 
-pub const CHUNK_HEIGHT: usize = 256;
 pub const CHUNK_WIDTH: usize = 16;
 
 pub const SECTION_HEIGHT: usize = 16;
 pub const SECTION_WIDTH: usize = CHUNK_WIDTH;
 pub const SECTION_VOLUME: usize = (SECTION_HEIGHT * SECTION_WIDTH * SECTION_WIDTH) as usize;
-pub const NUM_SECTIONS: usize = 16;
 
 pub const GLOBAL_BITS_PER_BLOCK: u8 = 15;
 pub const MIN_BITS_PER_BLOCK: u8 = 4;
-pub const MAX_BITS_PER_BLOCK: u8 = 8;
\ No newline at end of file
+pub const MAX_BITS_PER_BLOCK: u8 = 8;
+
+pub const BIOME_WIDTH: usize = 4; // Biomes are stored per 4x4x4 blocks, a quart
+pub const BIOME_VOLUME: usize = BIOME_WIDTH * BIOME_WIDTH * BIOME_WIDTH;
+pub const MIN_BITS_PER_BIOME: u8 = 1;
+pub const MAX_BITS_PER_BIOME: u8 = 3;
+
+pub const DEFAULT_BIOME: Biome = Biome::Plains;
+
+mod paletted_container;
+pub use paletted_container::*;
+
+mod biome_source;
+pub use biome_source::*;
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::{
+        atomic::{AtomicU16, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use crate::{
+    biome_registry::BiomeRegistry, biomes::Biome, block_state::BlockState, blocks::Block, data,
+    dimension::DimensionType,
+};
+
+pub trait ChunkGenerator: Send + Sync {
+    /// Runs before generate_chunk, chunks are all DEFAULT_BIOME until then.
+    fn generate_biomes(&self, _chunk: Arc<Chunk>) {}
+
+    fn generate_chunk(&self, chunk: Arc<Chunk>);
+}
+
+// Sections store a biome as its position in Biome::ALL, the synced registry gives it its network id when sent
+fn biome_value(biome: Biome) -> u32 {
+    Biome::ALL.iter().position(|entry| *entry == biome).unwrap_or_default() as u32
+}
+
+fn value_biome(value: u32) -> Biome {
+    Biome::ALL.get(value as usize).copied().unwrap_or(DEFAULT_BIOME)
+}
+
+/// Represents a 16x16x16 chunk section.
+pub struct ChunkSection {
+    block_states: RwLock<PalettedContainer>,
+    biomes: RwLock<PalettedContainer>, // One per quart
+    block_count: AtomicU16, // Non-air blocks (cave and void air are air too), kept up to date by set_block_state
+}
+
+impl Default for ChunkSection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkSection {
+    pub fn new() -> Self {
+        Self {
+            block_states: RwLock::new(PalettedContainer::blocks(BlockState::AIR)),
+            biomes: RwLock::new(PalettedContainer::biomes(biome_value(DEFAULT_BIOME))),
+            block_count: AtomicU16::new(0),
+        }
+    }
+
+    // Same order as the protocol, x first then z then y
+    fn index(x: u8, y: u8, z: u8) -> usize {
+        ((y as usize) << 8) | ((z as usize) << 4) | x as usize
+    }
+
+    /// Places the default state of `block`.
+    pub fn set_block(&self, x: u8, y: u8, z: u8, block: Block) {
+        self.set_block_state(x, y, z, block.default_state());
+    }
+
+    pub fn set_block_state(&self, x: u8, y: u8, z: u8, state: BlockState) {
+        if x < 16 && y < 16 && z < 16 {
+            let previous = self.block_states.write().unwrap().set(Self::index(x, y, z), state.id());
+            let previous = BlockState::from_id(previous).unwrap_or_default();
+
+            match (previous.is_air(), state.is_air()) {
+                (true, false) => self.block_count.fetch_add(1, Ordering::Relaxed),
+                (false, true) => self.block_count.fetch_sub(1, Ordering::Relaxed),
+                _ => 0,
+            };
+        }
+    }
+
+    /// The block state at the given position, air outside of the section.
+    pub fn get_block_state(&self, x: u8, y: u8, z: u8) -> BlockState {
+        if x < 16 && y < 16 && z < 16 {
+            let id = self.block_states.read().unwrap().get(Self::index(x, y, z));
+            BlockState::from_id(id).unwrap_or_default()
+        } else {
+            BlockState::AIR
+        }
+    }
+
+    pub fn block_count(&self) -> u16 {
+        self.block_count.load(Ordering::Relaxed)
+    }
+
+    // Same order as blocks, at a quarter of the resolution
+    fn biome_index(x: u8, y: u8, z: u8) -> usize {
+        ((y as usize) << 4) | ((z as usize) << 2) | x as usize
+    }
+
+    /// Sets the biome of a quart, `x`, `y` and `z` are below 4.
+    pub fn set_biome(&self, x: u8, y: u8, z: u8, biome: Biome) {
+        if x < 4 && y < 4 && z < 4 {
+            self.biomes.write().unwrap().set(Self::biome_index(x, y, z), biome_value(biome));
+        }
+    }
+
+    /// The biome of a quart, DEFAULT_BIOME outside of the section.
+    pub fn get_biome(&self, x: u8, y: u8, z: u8) -> Biome {
+        if x < 4 && y < 4 && z < 4 {
+            value_biome(self.biomes.read().unwrap().get(Self::biome_index(x, y, z)))
+        } else {
+            DEFAULT_BIOME
+        }
+    }
+
+    pub fn fill_biome(&self, biome: Biome) {
+        self.biomes.write().unwrap().fill(biome_value(biome));
+    }
+
+    /// Writes the section the way Chunk Data expects it: the non-air block count, block states, then biomes.
+    pub fn write(&self, buffer: &mut Vec<u8>, biomes: &BiomeRegistry) -> Result<(), Box<Error>> {
+        data::write_ushort(buffer, self.block_count());
+        self.block_states.read().unwrap().write(buffer);
+        self.write_biomes(buffer, biomes)
+    }
+
+    /// Just the biomes, with their ids in `biomes`.
+    pub fn write_biomes(&self, buffer: &mut Vec<u8>, biomes: &BiomeRegistry) -> Result<(), Box<Error>> {
+        self.biomes
+            .read()
+            .unwrap()
+            .write_mapped(buffer, biomes.global_bits(), |value| biomes.network_id(value_biome(value)))
+    }
+}
+
+/// A 16 block wide column spanning the whole height of its dimension, one section per 16 blocks from min_y up.
+pub struct Chunk {
+    pub x: i32,
+    pub z: i32,
+    min_y: i32,
+    height: i32,
+    sections: Vec<Arc<ChunkSection>>, // From the bottom up, height / 16 of them
+}
+
+impl Chunk {
+    pub fn new(x: i32, z: i32, dimension: &DimensionType) -> Self {
+        // Rounded up so the top blocks still have a section, the bounds stay where the dimension put them
+        let section_count = (dimension.height.max(0) as usize).div_ceil(SECTION_HEIGHT);
+
+        let mut sections = Vec::with_capacity(section_count);
+        for _ in 0..section_count {
+            sections.push(Arc::new(ChunkSection::new()));
+        }
+
+        Self { x, z, min_y: dimension.min_y, height: dimension.height, sections }
+    }
+
+    pub fn coordinates(&self) -> (i32, i32) {
+        (self.x, self.z)
+    }
+
+    /// The lowest block Y, inclusive.
+    pub fn min_y(&self) -> i32 {
+        self.min_y
+    }
+
+    /// The highest block Y, exclusive.
+    pub fn max_y(&self) -> i32 {
+        self.min_y + self.height
+    }
+
+    pub fn sections(&self) -> &[Arc<ChunkSection>] {
+        &self.sections
+    }
+
+    // The section holding the block and its coordinates within it
+    fn locate(&self, x: u8, y: i32, z: u8) -> Result<(&ChunkSection, u8), Box<Error>> {
+        if x as usize >= CHUNK_WIDTH || z as usize >= CHUNK_WIDTH {
+            return Err(Box::new(Error::new(
+                ErrorKind::InvalidInput,
+                format!("X and Z must be below {} within a chunk, got {} and {}", CHUNK_WIDTH, x, z),
+            )));
+        }
+        if y < self.min_y() || y >= self.max_y() {
+            return Err(Box::new(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Y {} is outside of the chunk, which spans {}..{}", y, self.min_y(), self.max_y()),
+            )));
+        }
+
+        let y = (y - self.min_y) as usize;
+        Ok((&self.sections[y / SECTION_HEIGHT], (y % SECTION_HEIGHT) as u8))
+    }
+
+    /// Sets the block at `y` in world coordinates, `x` and `z` are within the chunk.
+    pub fn set_block(&self, x: u8, y: i32, z: u8, state: impl Into<BlockState>) -> Result<(), Box<Error>> {
+        let (section, section_y) = self.locate(x, y, z)?;
+        section.set_block_state(x, section_y, z, state.into());
+        Ok(())
+    }
+
+    pub fn get_block(&self, x: u8, y: i32, z: u8) -> Result<BlockState, Box<Error>> {
+        let (section, section_y) = self.locate(x, y, z)?;
+        Ok(section.get_block_state(x, section_y, z))
+    }
+
+    // The section holding the quart and its coordinates within it, `y` is the world Y divided by 4
+    fn locate_biome(&self, x: u8, y: i32, z: u8) -> Result<(&ChunkSection, u8), Box<Error>> {
+        let quarts = (CHUNK_WIDTH / BIOME_WIDTH) as u8;
+        if x >= quarts || z >= quarts {
+            return Err(Box::new(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Quart X and Z must be below {} within a chunk, got {} and {}", quarts, x, z),
+            )));
+        }
+
+        let (min_y, max_y) = (self.min_y() >> 2, self.max_y() >> 2);
+        if y < min_y || y >= max_y {
+            return Err(Box::new(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Quart Y {} is outside of the chunk, which spans {}..{}", y, min_y, max_y),
+            )));
+        }
+
+        let y = (y - min_y) as usize;
+        let per_section = SECTION_HEIGHT / BIOME_WIDTH;
+        Ok((&self.sections[y / per_section], (y % per_section) as u8))
+    }
+
+    /// Sets the biome of a quart, 4x4x4 blocks. `y` is in world quarts (the world Y divided by 4), `x` and `z` are
+    /// within the chunk.
+    pub fn set_biome(&self, x: u8, y: i32, z: u8, biome: Biome) -> Result<(), Box<Error>> {
+        let (section, section_y) = self.locate_biome(x, y, z)?;
+        section.set_biome(x, section_y, z, biome);
+        Ok(())
+    }
+
+    pub fn get_biome(&self, x: u8, y: i32, z: u8) -> Result<Biome, Box<Error>> {
+        let (section, section_y) = self.locate_biome(x, y, z)?;
+        Ok(section.get_biome(x, section_y, z))
+    }
+
+    /// The Data field of Chunk Data and Update Light, every section from the bottom up.
+    pub fn write_sections(&self, buffer: &mut Vec<u8>, biomes: &BiomeRegistry) -> Result<(), Box<Error>> {
+        for section in &self.sections {
+            section.write(buffer, biomes)?;
+        }
+        Ok(())
+    }
+
+    /// The data of the chunk in Chunk Biomes, the biomes of every section from the bottom up.
+    pub fn write_biomes(&self, buffer: &mut Vec<u8>, biomes: &BiomeRegistry) -> Result<(), Box<Error>> {
+        for section in &self.sections {
+            section.write_biomes(buffer, biomes)?;
+        }
+        Ok(())
+    }
+}
+
+// Example module, delete later (MARK_DELETE)
+pub mod example {
+    use std::sync::Arc;
+
+    use crate::{blocks::Block, chunk::{Chunk, ChunkGenerator}};
+
+    pub struct SinewaveGenerator;
+
+    impl ChunkGenerator for SinewaveGenerator {
+        fn generate_chunk(&self, chunk: Arc<Chunk>) {
+            let (chunk_x, chunk_z) = chunk.coordinates();
+
+            for x in 0..16 {
+                for z in 0..16 {
+                    let world_x = chunk_x * 16 + x;
+                    let world_z = chunk_z * 16 + z;
+
+                    let height = (40.0 + (world_x as f64 * 0.1).sin() * (world_z as f64 * 0.1).cos() * 10.0) as i32;
+
+                    // Whatever doesn't fit in the dimension is left out
+                    for y in chunk.min_y().max(0)..=height.min(chunk.max_y() - 1) {
+                        let _ = chunk.set_block(x as u8, y, z as u8, Block::GrassBlock);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The overworld's bounds, -64 up to 320
+    fn overworld() -> DimensionType {
+        DimensionType {
+            piglin_safe: false,
+            natural: true,
+            ambient_light: 0.0,
+            fixed_time: None,
+            infiniburn: "#minecraft:infiniburn_overworld".to_string(),
+            respawn_anchor_works: false,
+            bed_works: true,
+            effects: "minecraft:overworld".to_string(),
+            has_skylight: true,
+            has_ceiling: false,
+            ultrawarm: false,
+            has_raids: true,
+            logical_height: 384,
+            coordinate_scale: 1.0,
+            min_y: -64,
+            height: 384,
+            monster_spawn_light_level: Some(7),
+            monster_spawn_block_light_limit: 0,
+        }
+    }
+
+    #[test]
+    fn spans_the_dimension_height() {
+        let chunk = Chunk::new(0, 0, &overworld());
+
+        assert_eq!(chunk.min_y(), -64);
+        assert_eq!(chunk.max_y(), 320);
+        assert_eq!(chunk.sections().len(), 24);
+    }
+
+    #[test]
+    fn rounds_partial_sections_up() {
+        let dimension = DimensionType { min_y: 0, height: 100, ..overworld() };
+        let chunk = Chunk::new(0, 0, &dimension);
+
+        assert_eq!(chunk.sections().len(), 7);
+        assert_eq!(chunk.max_y(), 100);
+
+        chunk.set_block(15, 99, 15, Block::Stone).unwrap();
+        assert_eq!(chunk.get_block(15, 99, 15).unwrap(), Block::Stone.default_state());
+        assert_eq!(chunk.sections()[6].get_block_state(15, 3, 15), Block::Stone.default_state());
+        assert!(chunk.set_block(0, 100, 0, Block::Stone).is_err());
+    }
+
+    #[test]
+    fn has_no_sections_without_a_height() {
+        for height in [0, -16] {
+            let dimension = DimensionType { height, ..overworld() };
+            let chunk = Chunk::new(0, 0, &dimension);
+
+            assert!(chunk.sections().is_empty());
+            assert!(chunk.get_block(0, dimension.min_y, 0).is_err());
+        }
+    }
+
+    #[test]
+    fn sets_blocks_at_both_ends() {
+        let chunk = Chunk::new(0, 0, &overworld());
+        let bottom = chunk.min_y();
+        let top = chunk.max_y() - 1;
+
+        chunk.set_block(1, bottom, 2, Block::Bedrock).unwrap();
+        chunk.set_block(3, top, 4, Block::Stone).unwrap();
+
+        assert_eq!(chunk.get_block(1, bottom, 2).unwrap(), Block::Bedrock.default_state());
+        assert_eq!(chunk.get_block(3, top, 4).unwrap(), Block::Stone.default_state());
+        assert_eq!(chunk.get_block(1, bottom + 1, 2).unwrap(), BlockState::AIR);
+
+        // The lowest block is at the bottom of the first section, the highest at the top of the last
+        assert_eq!(chunk.sections()[0].get_block_state(1, 0, 2), Block::Bedrock.default_state());
+        assert_eq!(chunk.sections()[23].get_block_state(3, 15, 4), Block::Stone.default_state());
+        assert_eq!(chunk.sections()[0].block_count(), 1);
+        assert_eq!(chunk.sections()[23].block_count(), 1);
+
+        // Y 0 is 64 blocks up, the start of the fifth section
+        chunk.set_block(0, 0, 0, Block::Dirt).unwrap();
+        assert_eq!(chunk.sections()[4].get_block_state(0, 0, 0), Block::Dirt.default_state());
+    }
+
+    #[test]
+    fn rejects_blocks_outside_the_chunk() {
+        let chunk = Chunk::new(0, 0, &overworld());
+
+        for y in [chunk.min_y() - 1, chunk.max_y(), i32::MIN, i32::MAX] {
+            assert_eq!(chunk.get_block(0, y, 0).unwrap_err().kind(), ErrorKind::InvalidInput);
+            assert_eq!(chunk.set_block(0, y, 0, Block::Stone).unwrap_err().kind(), ErrorKind::InvalidInput);
+        }
+        assert!(chunk.get_block(16, 0, 0).is_err());
+        assert!(chunk.get_block(0, 0, 16).is_err());
+
+        // Nothing was written anywhere
+        assert!(chunk.sections().iter().all(|section| section.block_count() == 0));
+    }
+
+    #[test]
+    fn bounds_biomes_by_quart() {
+        let chunk = Chunk::new(0, 0, &overworld());
+        let (bottom, top) = (chunk.min_y() >> 2, (chunk.max_y() >> 2) - 1);
+
+        chunk.set_biome(0, bottom, 0, Biome::Desert).unwrap();
+        chunk.set_biome(3, top, 3, Biome::Forest).unwrap();
+
+        assert_eq!(chunk.get_biome(0, bottom, 0).unwrap(), Biome::Desert);
+        assert_eq!(chunk.get_biome(3, top, 3).unwrap(), Biome::Forest);
+        assert!(chunk.get_biome(0, bottom - 1, 0).is_err());
+        assert!(chunk.get_biome(0, top + 1, 0).is_err());
+        assert!(chunk.get_biome(4, 0, 0).is_err());
+    }
+}
@@ -0,0 +1,367 @@
+use crate::{biome_registry::bits_for, biomes::Biome, block_state::BlockState, data};
+
+use super::{
+    BIOME_VOLUME, GLOBAL_BITS_PER_BLOCK, MAX_BITS_PER_BIOME, MAX_BITS_PER_BLOCK, MIN_BITS_PER_BIOME,
+    MIN_BITS_PER_BLOCK, SECTION_VOLUME,
+};
+
+//
+// How the protocol stores the block states and biomes of a section. A section holding one value sends just that value, a few
+// values are sent as a palette with indices into it, and past max_bits the global ids are stored directly.
+// Entries are packed into longs from the least significant bit up and never span two longs.
+//
+
+#[derive(Clone, Debug)]
+pub struct PalettedContainer {
+    size: usize,
+    min_bits: u8,
+    max_bits: u8, // Largest palette indices, anything more goes direct
+    global_bits: u8,
+    storage: Storage,
+}
+
+#[derive(Clone, Debug)]
+enum Storage {
+    SingleValue(u32),
+    Indirect { palette: Vec<u32>, data: BitStorage },
+    Direct(BitStorage),
+}
+
+impl PalettedContainer {
+    /// A container of `size` entries all set to `value`.
+    pub fn new(size: usize, min_bits: u8, max_bits: u8, global_bits: u8, value: u32) -> Self {
+        Self { size, min_bits, max_bits, global_bits, storage: Storage::SingleValue(value) }
+    }
+
+    /// The block states of a section, all set to `state`.
+    pub fn blocks(state: BlockState) -> Self {
+        Self::new(SECTION_VOLUME, MIN_BITS_PER_BLOCK, MAX_BITS_PER_BLOCK, GLOBAL_BITS_PER_BLOCK, state.id())
+    }
+
+    /// The biomes of a section, all set to `value` (a position in Biome::ALL).
+    pub fn biomes(value: u32) -> Self {
+        Self::new(BIOME_VOLUME, MIN_BITS_PER_BIOME, MAX_BITS_PER_BIOME, bits_for(Biome::ALL.len()), value)
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// What is sent as Bits Per Entry, 0 while the container holds a single value.
+    pub fn bits_per_entry(&self) -> u8 {
+        match &self.storage {
+            Storage::SingleValue(_) => 0,
+            Storage::Indirect { data, .. } | Storage::Direct(data) => data.bits,
+        }
+    }
+
+    pub fn get(&self, index: usize) -> u32 {
+        assert!(index < self.size, "Index {} out of bounds for a container of {}", index, self.size);
+
+        match &self.storage {
+            Storage::SingleValue(value) => *value,
+            Storage::Indirect { palette, data } => palette[data.get(index) as usize],
+            Storage::Direct(data) => data.get(index),
+        }
+    }
+
+    /// Sets the entry at `index` and returns what it was, growing the palette or going direct when needed.
+    pub fn set(&mut self, index: usize, value: u32) -> u32 {
+        let previous = self.get(index);
+        if previous == value {
+            return previous;
+        }
+
+        match &mut self.storage {
+            Storage::SingleValue(_) => {
+                let mut data = BitStorage::new(self.min_bits, self.size);
+                data.set(index, 1);
+                self.storage = Storage::Indirect { palette: vec![previous, value], data };
+            }
+            Storage::Indirect { palette, data } => {
+                if let Some(palette_index) = palette.iter().position(|entry| *entry == value) {
+                    data.set(index, palette_index as u32);
+                } else if palette.len() < 1 << data.bits {
+                    palette.push(value);
+                    data.set(index, (palette.len() - 1) as u32);
+                } else {
+                    self.grow();
+                    return self.set(index, value);
+                }
+            }
+            Storage::Direct(data) => data.set(index, value),
+        }
+
+        previous
+    }
+
+    /// Sets every entry to `value`, back to a single value.
+    pub fn fill(&mut self, value: u32) {
+        self.storage = Storage::SingleValue(value);
+    }
+
+    /// How many entries `predicate` holds for.
+    pub fn count(&self, predicate: impl Fn(u32) -> bool) -> usize {
+        match &self.storage {
+            Storage::SingleValue(value) => if predicate(*value) { self.size } else { 0 },
+            Storage::Indirect { palette, data } => {
+                let matching: Vec<bool> = palette.iter().map(|entry| predicate(*entry)).collect();
+                (0..self.size).filter(|index| matching[data.get(*index) as usize]).count()
+            }
+            Storage::Direct(data) => (0..self.size).filter(|index| predicate(data.get(*index))).count(),
+        }
+    }
+
+    // One more bit for the palette, or the global ids once the palette gets too large
+    fn grow(&mut self) {
+        let Storage::Indirect { palette, data } = &self.storage else {
+            return;
+        };
+
+        let bits = data.bits + 1;
+        self.storage = if bits > self.max_bits {
+            let mut direct = BitStorage::new(self.global_bits, self.size);
+            for index in 0..self.size {
+                direct.set(index, palette[data.get(index) as usize]);
+            }
+            Storage::Direct(direct)
+        } else {
+            let mut resized = BitStorage::new(bits, self.size);
+            for index in 0..self.size {
+                resized.set(index, data.get(index));
+            }
+            Storage::Indirect { palette: palette.clone(), data: resized }
+        };
+    }
+
+    /// Writes the container as the protocol expects it. Since 1.21.5 the length of the data array isn't sent,
+    /// the client works it out from the bits per entry.
+    pub fn write(&self, buffer: &mut Vec<u8>) {
+        match &self.storage {
+            Storage::SingleValue(value) => {
+                data::write_byte(buffer, 0);
+                data::write_varint(buffer, *value);
+            }
+            Storage::Indirect { palette, data } => {
+                data::write_byte(buffer, data.bits);
+                data::write_varint(buffer, palette.len() as u32);
+                for entry in palette {
+                    data::write_varint(buffer, *entry);
+                }
+                data.write(buffer);
+            }
+            Storage::Direct(data) => {
+                data::write_byte(buffer, data.bits);
+                data.write(buffer);
+            }
+        }
+    }
+
+    /// Writes the container with every value passed through `map`, for values that only get their network id once
+    /// they're sent. A container past its palette is written with `global_bits` per entry.
+    pub fn write_mapped<E>(
+        &self,
+        buffer: &mut Vec<u8>,
+        global_bits: u8,
+        map: impl Fn(u32) -> Result<u32, E>,
+    ) -> Result<(), E> {
+        match &self.storage {
+            Storage::SingleValue(value) => {
+                data::write_byte(buffer, 0);
+                data::write_varint(buffer, map(*value)?);
+            }
+            Storage::Indirect { palette, data } => {
+                data::write_byte(buffer, data.bits);
+                data::write_varint(buffer, palette.len() as u32);
+                for entry in palette {
+                    data::write_varint(buffer, map(*entry)?);
+                }
+                data.write(buffer);
+            }
+            Storage::Direct(data) => {
+                let mut mapped = BitStorage::new(global_bits, self.size);
+                for index in 0..self.size {
+                    mapped.set(index, map(data.get(index))?);
+                }
+                data::write_byte(buffer, global_bits);
+                mapped.write(buffer);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Fixed size entries packed into longs
+#[derive(Clone, Debug)]
+struct BitStorage {
+    bits: u8,
+    longs: Vec<u64>,
+}
+
+impl BitStorage {
+    fn new(bits: u8, size: usize) -> Self {
+        let per_long = 64 / bits as usize;
+        Self { bits, longs: vec![0; size.div_ceil(per_long)] }
+    }
+
+    fn mask(&self) -> u64 {
+        (1 << self.bits) - 1
+    }
+
+    // The long holding `index` and how far into it the entry starts
+    fn locate(&self, index: usize) -> (usize, usize) {
+        let per_long = 64 / self.bits as usize;
+        (index / per_long, (index % per_long) * self.bits as usize)
+    }
+
+    fn get(&self, index: usize) -> u32 {
+        let (long, shift) = self.locate(index);
+        ((self.longs[long] >> shift) & self.mask()) as u32
+    }
+
+    fn set(&mut self, index: usize, value: u32) {
+        let (long, shift) = self.locate(index);
+        let mask = self.mask();
+        self.longs[long] = (self.longs[long] & !(mask << shift)) | ((value as u64 & mask) << shift);
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        for long in &self.longs {
+            data::write_long(buffer, *long);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks() -> PalettedContainer {
+        PalettedContainer::blocks(BlockState::AIR)
+    }
+
+    // Sets the first `count` entries to 1..=count, so the container holds count + 1 values
+    fn with_values(count: u32) -> PalettedContainer {
+        let mut container = blocks();
+        for value in 1..=count {
+            container.set(value as usize - 1, value);
+        }
+        container
+    }
+
+    fn written(container: &PalettedContainer) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        container.write(&mut buffer);
+        buffer
+    }
+
+    #[test]
+    fn goes_from_single_value_to_indirect_to_direct() {
+        let mut container = blocks();
+        assert_eq!(container.bits_per_entry(), 0);
+
+        container.set(0, 1);
+        assert!(matches!(&container.storage, Storage::Indirect { palette, .. } if palette.len() == 2));
+        assert_eq!(container.bits_per_entry(), MIN_BITS_PER_BLOCK);
+
+        // 16 values still fit in 4 bits, the 17th needs a fifth
+        let mut container = with_values(15);
+        assert_eq!(container.bits_per_entry(), 4);
+        container.set(15, 16);
+        assert_eq!(container.bits_per_entry(), 5);
+        assert!(matches!(&container.storage, Storage::Indirect { palette, .. } if palette.len() == 17));
+
+        let mut container = with_values(255);
+        assert_eq!(container.bits_per_entry(), MAX_BITS_PER_BLOCK);
+        container.set(255, 256);
+        assert!(matches!(container.storage, Storage::Direct(_)));
+        assert_eq!(container.bits_per_entry(), GLOBAL_BITS_PER_BLOCK);
+
+        // Growing keeps every entry
+        for index in 0..SECTION_VOLUME {
+            let expected = if index < 256 { index as u32 + 1 } else { 0 };
+            assert_eq!(container.get(index), expected);
+        }
+
+        container.fill(7);
+        assert_eq!(container.bits_per_entry(), 0);
+        assert_eq!(container.get(SECTION_VOLUME - 1), 7);
+    }
+
+    #[test]
+    fn writes_a_single_value() {
+        assert_eq!(written(&blocks()), [0, 0]);
+        assert_eq!(written(&PalettedContainer::blocks(BlockState::from_id(300).unwrap())), [0, 0xAC, 0x02]);
+    }
+
+    #[test]
+    fn writes_a_palette_without_the_data_length() {
+        let mut container = blocks();
+        container.set(0, 1);
+        container.set(1, 300);
+        container.set(16, 1);
+
+        let buffer = written(&container);
+
+        // Bits per entry, then the palette length and the palette, then straight into the longs
+        assert_eq!(&buffer[..6], &[4, 3, 0, 1, 0xAC, 0x02]);
+        let longs = &buffer[6..];
+        assert_eq!(longs.len(), SECTION_VOLUME / 16 * 8);
+
+        // 16 entries of 4 bits to a long, the first one in the lowest bits
+        assert_eq!(&longs[..8], &0x21u64.to_be_bytes());
+        assert_eq!(&longs[8..16], &1u64.to_be_bytes());
+        assert!(longs[16..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn writes_global_ids_without_a_palette() {
+        let mut container = with_values(256);
+        let buffer = written(&container);
+
+        // 4 entries of 15 bits to a long, the last 4 bits are left empty
+        assert_eq!(buffer[0], GLOBAL_BITS_PER_BLOCK);
+        assert_eq!(buffer.len(), 1 + SECTION_VOLUME / 4 * 8);
+        let first: u64 = 1 | 2 << 15 | 3 << 30 | 4 << 45;
+        assert_eq!(&buffer[1..9], &first.to_be_bytes());
+
+        container.set(3, 0x7FFF);
+        let buffer = written(&container);
+        let first: u64 = 1 | 2 << 15 | 3 << 30 | 0x7FFF << 45;
+        assert_eq!(&buffer[1..9], &first.to_be_bytes());
+    }
+
+    #[test]
+    fn writes_mapped_biomes() {
+        let mut container = PalettedContainer::biomes(0);
+        container.set(63, 2);
+
+        let mut buffer = Vec::new();
+        container.write_mapped::<()>(&mut buffer, 6, |value| Ok(value + 10)).unwrap();
+
+        // 64 entries of 1 bit fit in a single long
+        assert_eq!(&buffer[..4], &[MIN_BITS_PER_BIOME, 2, 10, 12]);
+        assert_eq!(&buffer[4..], &(1u64 << 63).to_be_bytes());
+    }
+
+    #[test]
+    fn round_trips_every_index() {
+        for distinct in [2, 16, 17, 256, 257, 1 << GLOBAL_BITS_PER_BLOCK] {
+            let mut container = blocks();
+            let value = |index: usize| (index as u32 * 7919) % distinct;
+
+            for index in 0..SECTION_VOLUME {
+                assert_eq!(container.set(index, value(index)), 0);
+            }
+            for index in 0..SECTION_VOLUME {
+                assert_eq!(container.get(index), value(index), "index {} with {} values", index, distinct);
+            }
+
+            // Setting back what was there changes nothing
+            for index in 0..SECTION_VOLUME {
+                assert_eq!(container.set(index, value(index)), value(index));
+            }
+            assert_eq!(container.count(|entry| entry == 0), (0..SECTION_VOLUME).filter(|i| value(*i) == 0).count());
+        }
+    }
+}
@@ -1,6 +1,6 @@
 // Common structs between the clientbound and the serverbound packets during the Configuration state
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct ConfigKnownPackEntry {
     pub name: String, // Namespaced
     pub version: String,
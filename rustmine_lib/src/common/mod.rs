@@ -1 +1,2 @@
-pub mod configuration_state;
\ No newline at end of file
+pub mod configuration_state;
+pub mod position;
\ No newline at end of file
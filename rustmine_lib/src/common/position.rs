@@ -0,0 +1,30 @@
+// Block coordinates, packed into a single long on the wire (x: 26 bits, z: 26 bits, y: 12 bits)
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BlockPosition {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl BlockPosition {
+    pub fn new(x: i32, y: i32, z: i32) -> BlockPosition {
+        BlockPosition { x, y, z }
+    }
+
+    pub fn from_packed(value: u64) -> BlockPosition {
+        let value = value as i64;
+
+        BlockPosition {
+            x: (value >> 38) as i32,
+            y: (value << 52 >> 52) as i32,
+            z: (value << 26 >> 38) as i32,
+        }
+    }
+
+    pub fn packed(&self) -> u64 {
+        (((self.x as u64) & 0x3FFFFFF) << 38)
+            | (((self.z as u64) & 0x3FFFFFF) << 12)
+            | ((self.y as u64) & 0xFFF)
+    }
+}
@@ -1,12 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Component {
     Text(TextComponent),
     Score(ScoreComponent),
     Selector(SelectorComponent),
     Keybind(KeybindComponent),
+    #[serde(rename = "translatable")] // What the client expects, not the lowercased variant name
     Translation(TranslationComponent),
 }
 
@@ -21,9 +22,23 @@ impl Component {
         }
         self
     }
+
+    /// The text without any styling, for places that can't show components.
+    /// Translations fall back to their fallback or key since there is no language to look them up in.
+    pub fn plain_text(&self) -> String {
+        let (own, extra) = match self {
+            Component::Text(c) => (c.text.clone(), &c.extra),
+            Component::Score(c) => (String::new(), &c.extra),
+            Component::Selector(c) => (c.selector.clone(), &c.extra),
+            Component::Keybind(c) => (c.keybind.clone(), &c.extra),
+            Component::Translation(c) => (c.fallback.clone().unwrap_or_else(|| c.translate.clone()), &c.extra),
+        };
+
+        extra.iter().fold(own, |text, child| text + &child.plain_text())
+    }
 }
 
-#[derive(Serialize, Deserialize, Default, Clone)]
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct Style {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bold: Option<bool>,
@@ -51,13 +66,18 @@ impl Style {
         self
     }
 
+    pub fn italic(mut self, val: bool) -> Self {
+        self.italic = Some(val);
+        self
+    }
+
     pub fn color(mut self, val: impl Into<String>) -> Self {
         self.color = Some(val.into());
         self
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TextComponent {
     pub text: String,
     #[serde(flatten)]
@@ -66,7 +86,7 @@ pub struct TextComponent {
     pub extra: Vec<Component>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TranslationComponent {
     pub translate: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -79,7 +99,7 @@ pub struct TranslationComponent {
     pub extra: Vec<Component>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ScoreComponent {
     pub score: ScoreData,
     #[serde(flatten)]
@@ -88,13 +108,13 @@ pub struct ScoreComponent {
     pub extra: Vec<Component>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ScoreData {
     pub name: String,
     pub objective: String,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SelectorComponent {
     pub selector: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -105,7 +125,7 @@ pub struct SelectorComponent {
     pub extra: Vec<Component>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct KeybindComponent {
     pub keybind: String,
     #[serde(flatten)]
@@ -167,7 +187,9 @@ macro_rules! translation {
     ($key:expr) => {
         rustmine_lib::component::Component::Translation(
             rustmine_lib::component::TranslationComponent {
-                key: $key,
+                translate: $key.to_string(),
+                fallback: None,
+                with: None,
                 style: rustmine_lib::component::Style::default(),
                 extra: vec![],
             },
@@ -1,8 +1,10 @@
 use std::io::{Error, ErrorKind};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use uuid::Uuid;
 
+use crate::common::position::BlockPosition;
+
 pub fn write_varint(buffer: &mut Vec<u8>, mut value: u32) {
     while value >= 0x80 {
         buffer.push(((value & 0x7F) | 0x80) as u8);
@@ -27,7 +29,7 @@ pub fn write_uuid(buffer: &mut Vec<u8>, uuid: &Uuid) {
     buffer.extend_from_slice(uuid_bytes);
 }
 
-pub fn read_ushort(buffer: &Vec<u8>, position: &mut usize) -> Result<u16, Error> {
+pub fn read_ushort(buffer: &[u8], position: &mut usize) -> Result<u16, Error> {
     if *position + 2 > buffer.len() {
         return Err(Error::new(ErrorKind::Other, "Not enough bytes"));
     }
@@ -108,7 +110,7 @@ pub fn write_bool(buffer: &mut Vec<u8>, value: bool) {
     buffer.push(if value { 1 } else { 0 });
 }
 
-pub fn read_bool(buffer: &Vec<u8>, position: &mut usize) -> Result<bool, Error> {
+pub fn read_bool(buffer: &[u8], position: &mut usize) -> Result<bool, Error> {
     if *position >= buffer.len() {
         return Err(Error::new(ErrorKind::Other, "Not enough bytes"));
     }
@@ -135,7 +137,48 @@ pub fn read_long(buffer: &[u8], position: &mut usize) -> Result<u64, Error> {
     Ok(result)
 }
 
+pub fn write_int(buffer: &mut Vec<u8>, value: i32) {
+    buffer.extend_from_slice(&value.to_be_bytes());
+}
+
+pub fn read_int(buffer: &[u8], position: &mut usize) -> Result<i32, Error> {
+    if buffer.len() < *position + 4 {
+        return Err(Error::new(ErrorKind::UnexpectedEof, "Not enough bytes"));
+    }
+
+    let slice = &buffer[*position..*position + 4];
+    let result = i32::from_be_bytes(slice.try_into().unwrap());
+
+    *position += 4;
+    Ok(result)
+}
+
+pub fn write_float(buffer: &mut Vec<u8>, value: f32) {
+    buffer.extend_from_slice(&value.to_be_bytes());
+}
+
+pub fn read_float(buffer: &[u8], position: &mut usize) -> Result<f32, Error> {
+    read_int(buffer, position).map(|bits| f32::from_bits(bits as u32))
+}
+
+pub fn write_double(buffer: &mut Vec<u8>, value: f64) {
+    buffer.extend_from_slice(&value.to_be_bytes());
+}
+
+pub fn read_double(buffer: &[u8], position: &mut usize) -> Result<f64, Error> {
+    read_long(buffer, position).map(f64::from_bits)
+}
+
+pub fn write_position(buffer: &mut Vec<u8>, value: &BlockPosition) {
+    write_long(buffer, value.packed());
+}
+
+pub fn read_position(buffer: &[u8], position: &mut usize) -> Result<BlockPosition, Error> {
+    read_long(buffer, position).map(BlockPosition::from_packed)
+}
+
 use fastnbt::from_bytes;
+use fastnbt::{from_reader_with_opts, DeOpts};
 use fastnbt::to_bytes;
 
 pub fn write_nbt<T: Serialize>(buffer: &mut Vec<u8>, value: &T) -> Result<(), Error> {
@@ -157,6 +200,18 @@ pub fn read_nbt<'de, T: Deserialize<'de>>(
     Ok(result)
 }
 
+// The root tag has no name over the network, and only the bytes the tag takes are consumed
+pub fn read_network_nbt<T: DeserializeOwned>(buffer: &[u8], position: &mut usize) -> Result<T, Error> {
+    let mut rest = buffer.get(*position..).unwrap_or_default();
+    let available = rest.len();
+
+    let result: T = from_reader_with_opts(&mut rest, DeOpts::network_nbt())
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("NBT read error: {e}")))?;
+
+    *position += available - rest.len();
+    Ok(result)
+}
+
 pub fn read_byte(buffer: &[u8], position: &mut usize) -> Result<u8, Error> {
     if *position >= buffer.len() {
         return Err(Error::new(ErrorKind::UnexpectedEof, "Not enough bytes"));
@@ -65,6 +65,20 @@ impl DimensionTypeManager {
     pub fn get(&self, key: &str) -> Option<Arc<DimensionType>> {
         self.registered_types.read().unwrap().get(key).cloned()
     }
+
+    /// Every registered dimension type, sorted by key so the order (and with it the network ids) never changes.
+    pub fn entries(&self) -> Vec<(String, Arc<DimensionType>)> {
+        let mut entries = self
+            .registered_types
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(key, dim)| (key.clone(), dim.clone()))
+            .collect::<Vec<_>>();
+
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries
+    }
 }
 
 #[macro_export]
@@ -1,7 +1,18 @@
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+#[derive(Clone)]
 pub struct GameProfile {
     pub username: String,
     pub uuid: Uuid,
-    // Add properties
-}
\ No newline at end of file
+    pub properties: Vec<GameProfileProperty>,
+}
+
+// Signed by Mojang when it comes from the session server, "textures" is the one the client cares about
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct GameProfileProperty {
+    pub name: String,
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
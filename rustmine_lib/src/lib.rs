@@ -5,7 +5,11 @@ pub mod data;
 pub mod common;
 pub mod game_profile;
 pub mod chunk;
+pub mod block_state;
+pub mod biome_registry;
+pub mod tags;
 
 // Autogenerated outputs.
 pub mod blocks;
-pub mod biomes;
\ No newline at end of file
+pub mod biomes;
+pub mod vanilla_tags;
\ No newline at end of file
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use crate::blocks::{get_block_registry_entry, Block};
+use crate::vanilla_tags::VANILLA_TAGS;
+
+pub const BLOCK_REGISTRY: &str = "minecraft:block";
+pub const ITEM_REGISTRY: &str = "minecraft:item";
+pub const FLUID_REGISTRY: &str = "minecraft:fluid";
+pub const ENTITY_TYPE_REGISTRY: &str = "minecraft:entity_type";
+pub const GAME_EVENT_REGISTRY: &str = "minecraft:game_event";
+
+//
+// Tags by registry, each tag being a list of protocol ids in that registry.
+// Sorted maps so the packet comes out the same every time.
+//
+
+/// A tag's name and the protocol ids in it.
+pub type TagEntries = (String, Vec<u32>);
+
+#[derive(Default)]
+pub struct TagRegistry {
+    pub(crate) tags: RwLock<BTreeMap<String, BTreeMap<String, Vec<u32>>>>,
+}
+
+impl TagRegistry {
+    /// Every vanilla tag, the client relies on them for its own behaviour such as climbing and fluid rendering.
+    pub fn vanilla() -> Self {
+        let registry = TagRegistry::default();
+
+        for (registry_name, tags) in VANILLA_TAGS {
+            for (tag, ids) in *tags {
+                registry.add(registry_name, tag, ids.iter().copied());
+            }
+        }
+
+        registry
+    }
+
+    /// Adds `ids` to `tag`, creating it if needed. Ids already in the tag are skipped.
+    pub fn add(&self, registry: &str, tag: &str, ids: impl IntoIterator<Item = u32>) {
+        let mut tags = self.tags.write().unwrap();
+        let entries = tags
+            .entry(registry.to_string())
+            .or_default()
+            .entry(tag.to_string())
+            .or_default();
+
+        for id in ids {
+            if !entries.contains(&id) {
+                entries.push(id);
+            }
+        }
+    }
+
+    pub fn add_blocks(&self, tag: &str, blocks: &[Block]) {
+        let ids = blocks
+            .iter()
+            .map(|block| get_block_registry_entry(*block).protocol_id as u32);
+
+        self.add(BLOCK_REGISTRY, tag, ids);
+    }
+
+    pub fn remove(&self, registry: &str, tag: &str) -> Option<Vec<u32>> {
+        self.tags.write().unwrap().get_mut(registry)?.remove(tag)
+    }
+
+    pub fn get(&self, registry: &str, tag: &str) -> Option<Vec<u32>> {
+        self.tags.read().unwrap().get(registry)?.get(tag).cloned()
+    }
+
+    /// Every tag, grouped by registry.
+    pub fn entries(&self) -> Vec<(String, Vec<TagEntries>)> {
+        self.tags
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(registry, tags)| {
+                let tags = tags
+                    .iter()
+                    .map(|(tag, ids)| (tag.clone(), ids.clone()))
+                    .collect();
+
+                (registry.clone(), tags)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_ids(blocks: &[Block]) -> Vec<u32> {
+        blocks.iter().map(|block| get_block_registry_entry(*block).protocol_id as u32).collect()
+    }
+
+    #[test]
+    fn flattens_referenced_tags() {
+        let registry = TagRegistry::vanilla();
+        let climbable = registry.get(BLOCK_REGISTRY, "minecraft:climbable").unwrap();
+        let fall_damage_resetting = registry.get(BLOCK_REGISTRY, "minecraft:fall_damage_resetting").unwrap();
+
+        assert!(climbable.contains(&block_ids(&[Block::Ladder])[0]));
+        assert!(climbable.iter().all(|id| fall_damage_resetting.contains(id)));
+        assert!(block_ids(&[Block::SweetBerryBush, Block::Cobweb]).iter().all(|id| fall_damage_resetting.contains(id)));
+    }
+
+    #[test]
+    fn adds_each_id_once() {
+        let registry = TagRegistry::default();
+        registry.add_blocks("test:tag", &[Block::Stone, Block::Dirt]);
+        registry.add_blocks("test:tag", &[Block::Dirt, Block::Stone, Block::Cobweb]);
+
+        assert_eq!(
+            registry.get(BLOCK_REGISTRY, "test:tag").unwrap(),
+            block_ids(&[Block::Stone, Block::Dirt, Block::Cobweb])
+        );
+        assert_eq!(registry.remove(BLOCK_REGISTRY, "test:tag").unwrap().len(), 3);
+        assert_eq!(registry.get(BLOCK_REGISTRY, "test:tag"), None);
+    }
+}
@@ -0,0 +1,24 @@
+
+// AUTO-GENERATED FILE. DO NOT EDIT.
+
+// A tag's name and the protocol ids in it, tags it referenced are already flattened into it
+pub type VanillaTag = (&'static str, &'static [u32]);
+
+// Every vanilla tag by registry
+pub const VANILLA_TAGS: &[(&str, &[VanillaTag])] = &[
+    ("minecraft:block", &[
+        ("minecraft:climbable", &[208, 335, 805, 838, 839, 840, 841, 1044, 1045]),
+        ("minecraft:fall_damage_resetting", &[208, 335, 805, 838, 839, 840, 841, 1044, 1045, 821, 129]),
+        ("minecraft:fire", &[183, 184]),
+    ]),
+    ("minecraft:entity_type", &[
+    ]),
+    ("minecraft:fluid", &[
+        ("minecraft:lava", &[4, 3]),
+        ("minecraft:water", &[2, 1]),
+    ]),
+    ("minecraft:game_event", &[
+    ]),
+    ("minecraft:item", &[
+    ]),
+];
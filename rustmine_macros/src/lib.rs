@@ -0,0 +1,432 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Error, Expr, Fields, GenericArgument, Ident, LitInt, Path, PathArguments,
+    Type, parse_macro_input, spanned::Spanned,
+};
+
+//
+// #[derive(Packet)] generates the Packet impl of a struct along with the codec for the direction(s) it travels in.
+// Serverbound packets only get read_from and clientbound packets only get write_to, so using a packet the wrong way
+// around is a compile error instead of a runtime panic.
+//
+// #[packet(id = 0x00, state = login, direction = serverbound)]
+// #[packet(id = 0x03, state = configuration, direction = both)]
+// #[packet(id = 0x00, state = play, direction = clientbound, manual)] // Codec is written by hand
+//
+// Fields are encoded in declaration order through PacketField, unless told otherwise:
+//  #[packet(varint)]           VarInt through VarIntField
+//  #[packet(length_prefixed)]  Vec<T> prefixed by its length as a VarInt
+//  #[packet(optional)]         Option<T> prefixed by a boolean
+//  #[packet(nbt)]              Network NBT
+//  #[packet(json)]             JSON encoded string
+//  #[packet(remaining)]        Vec<u8> taking up the rest of the packet
+//  #[packet(with = path)]      path::read and path::write
+//
+// `optional` and `length_prefixed` can be combined with each other and with any of the other attributes, which then
+// apply to the innermost type.
+//
+
+#[proc_macro_derive(Packet, attributes(packet))]
+pub fn derive_packet(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Serverbound,
+    Clientbound,
+    Both,
+}
+
+struct PacketAttributes {
+    id: LitInt,
+    state: Ident,
+    direction: Direction,
+    manual: bool,
+}
+
+enum Leaf {
+    Field,
+    VarInt,
+    Nbt,
+    Json,
+    With(Path),
+}
+
+struct FieldAttributes {
+    leaf: Leaf,
+    optional: bool,
+    length_prefixed: bool,
+    remaining: bool,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let attributes = parse_packet_attributes(&input)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let id = &attributes.id;
+    let state = &attributes.state;
+
+    let mut output = quote! {
+        impl #impl_generics ::rustmine_server::packet::Packet for #name #ty_generics #where_clause {
+            fn id() -> u32 {
+                #id
+            }
+
+            fn state() -> ::rustmine_server::player::State {
+                ::rustmine_server::player::State::#state
+            }
+
+            fn packet_id(&self) -> u32 {
+                <Self as ::rustmine_server::packet::Packet>::id()
+            }
+        }
+    };
+
+    if attributes.manual {
+        return Ok(output);
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(Error::new(input.span(), "Packet can only be derived for structs")),
+    };
+
+    let mut readers = Vec::new();
+    let mut writers = Vec::new();
+    let mut names = Vec::new();
+
+    for field in fields.iter() {
+        let Some(field_name) = &field.ident else {
+            return Err(Error::new(field.span(), "Packet fields must be named"));
+        };
+
+        let field_attributes = parse_field_attributes(field)?;
+        let reader = read_expression(&field.ty, &field_attributes, field.span())?;
+        let writer = write_statement(
+            &field.ty,
+            &field_attributes,
+            quote!(&self.#field_name),
+            field.span(),
+        )?;
+
+        readers.push(quote! { let #field_name = #reader; });
+        writers.push(writer);
+        names.push(field_name.clone());
+    }
+
+    let construct = match fields {
+        Fields::Unit => quote!(#name),
+        _ => quote!(#name { #( #names ),* }),
+    };
+
+    if attributes.direction != Direction::Serverbound {
+        output.extend(quote! {
+            impl #impl_generics ::rustmine_server::packet::ClientboundPacket for #name #ty_generics #where_clause {
+                #[allow(unused_variables)]
+                fn write_to(&self, buffer: &mut Vec<u8>) {
+                    #( #writers )*
+                }
+            }
+        });
+    }
+
+    if attributes.direction != Direction::Clientbound {
+        output.extend(quote! {
+            impl #impl_generics ::rustmine_server::packet::ServerboundPacket for #name #ty_generics #where_clause {
+                #[allow(unused_variables, unused_mut)]
+                async fn read_from(
+                    id: u32,
+                    buffer: Vec<u8>,
+                ) -> Result<Box<Self>, Box<std::io::Error>> {
+                    if id != <Self as ::rustmine_server::packet::Packet>::id() {
+                        return Err(Box::new(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!(
+                                "Packet ID mismatch: expected {}, got {}",
+                                <Self as ::rustmine_server::packet::Packet>::id(),
+                                id
+                            ),
+                        )));
+                    }
+
+                    let mut position = 0usize;
+                    #( #readers )*
+
+                    Ok(Box::new(#construct))
+                }
+            }
+        });
+    }
+
+    Ok(output)
+}
+
+fn parse_packet_attributes(input: &DeriveInput) -> syn::Result<PacketAttributes> {
+    let mut id = None;
+    let mut state = None;
+    let mut direction = None;
+    let mut manual = false;
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("packet")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                id = Some(meta.value()?.parse::<LitInt>()?);
+            } else if meta.path.is_ident("state") {
+                let value = meta.value()?.parse::<Ident>()?;
+                let variant = match value.to_string().as_str() {
+                    "handshake" => "Handshake",
+                    "status" => "Status",
+                    "login" => "Login",
+                    "transfer" => "Transfer",
+                    "configuration" => "Configuration",
+                    "play" => "Play",
+                    _ => return Err(Error::new(value.span(), "Unknown packet state")),
+                };
+                state = Some(Ident::new(variant, value.span()));
+            } else if meta.path.is_ident("direction") {
+                let value = meta.value()?.parse::<Ident>()?;
+                direction = Some(match value.to_string().as_str() {
+                    "serverbound" => Direction::Serverbound,
+                    "clientbound" => Direction::Clientbound,
+                    "both" => Direction::Both,
+                    _ => {
+                        return Err(Error::new(
+                            value.span(),
+                            "Expected serverbound, clientbound or both",
+                        ));
+                    }
+                });
+            } else if meta.path.is_ident("manual") {
+                manual = true;
+            } else {
+                return Err(meta.error("Unknown packet attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let missing = |what: &str| Error::new(input.ident.span(), format!("Missing #[packet({} = ..)]", what));
+
+    Ok(PacketAttributes {
+        id: id.ok_or_else(|| missing("id"))?,
+        state: state.ok_or_else(|| missing("state"))?,
+        direction: direction.ok_or_else(|| missing("direction"))?,
+        manual,
+    })
+}
+
+fn parse_field_attributes(field: &syn::Field) -> syn::Result<FieldAttributes> {
+    let mut attributes = FieldAttributes {
+        leaf: Leaf::Field,
+        optional: false,
+        length_prefixed: false,
+        remaining: false,
+    };
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("packet")) {
+        attr.parse_nested_meta(|meta| {
+            let set_leaf = |attributes: &mut FieldAttributes, leaf: Leaf| {
+                if !matches!(attributes.leaf, Leaf::Field) {
+                    return Err(meta.error("Only one of varint, nbt, json or with can be used"));
+                }
+                attributes.leaf = leaf;
+                Ok(())
+            };
+
+            if meta.path.is_ident("varint") {
+                set_leaf(&mut attributes, Leaf::VarInt)?;
+            } else if meta.path.is_ident("nbt") {
+                set_leaf(&mut attributes, Leaf::Nbt)?;
+            } else if meta.path.is_ident("json") {
+                set_leaf(&mut attributes, Leaf::Json)?;
+            } else if meta.path.is_ident("with") {
+                let path = match meta.value()?.parse::<Expr>()? {
+                    Expr::Path(path) => path.path,
+                    Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) => lit.parse::<Path>()?,
+                    other => return Err(Error::new(other.span(), "Expected a path")),
+                };
+                set_leaf(&mut attributes, Leaf::With(path))?;
+            } else if meta.path.is_ident("optional") {
+                attributes.optional = true;
+            } else if meta.path.is_ident("length_prefixed") {
+                attributes.length_prefixed = true;
+            } else if meta.path.is_ident("remaining") {
+                attributes.remaining = true;
+            } else {
+                return Err(meta.error("Unknown field attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    if attributes.remaining
+        && (attributes.optional || attributes.length_prefixed || !matches!(attributes.leaf, Leaf::Field))
+    {
+        return Err(Error::new(field.span(), "remaining can't be combined with other attributes"));
+    }
+
+    Ok(attributes)
+}
+
+/// Returns `T` out of `Wrapper<T>`, used to look through `Option` and `Vec`.
+fn inner_type<'a>(ty: &'a Type, wrapper: &str, span: Span) -> syn::Result<&'a Type> {
+    if let Type::Path(path) = ty
+        && let Some(segment) = path.path.segments.last()
+        && segment.ident == wrapper
+        && let PathArguments::AngleBracketed(args) = &segment.arguments
+        && let Some(GenericArgument::Type(inner)) = args.args.first()
+    {
+        return Ok(inner);
+    }
+
+    Err(Error::new(span, format!("Expected a {}<T> field", wrapper)))
+}
+
+fn read_leaf(ty: &Type, leaf: &Leaf) -> TokenStream2 {
+    match leaf {
+        Leaf::Field => quote! {
+            <#ty as ::rustmine_server::packet::field::PacketField>::read(&buffer, &mut position)?
+        },
+        Leaf::VarInt => quote! {
+            <#ty as ::rustmine_server::packet::field::VarIntField>::read_varint(&buffer, &mut position)?
+        },
+        Leaf::Nbt => quote! {
+            ::rustmine_server::packet::field::read_nbt::<#ty>(&buffer, &mut position)?
+        },
+        Leaf::Json => quote! {
+            ::rustmine_server::packet::field::read_json::<#ty>(&buffer, &mut position)?
+        },
+        Leaf::With(path) => quote! {
+            #path::read(&buffer, &mut position)?
+        },
+    }
+}
+
+fn write_leaf(leaf: &Leaf, value: TokenStream2) -> TokenStream2 {
+    match leaf {
+        Leaf::Field => quote! {
+            ::rustmine_server::packet::field::PacketField::write(#value, buffer);
+        },
+        Leaf::VarInt => quote! {
+            ::rustmine_server::packet::field::VarIntField::write_varint(#value, buffer);
+        },
+        Leaf::Nbt => quote! {
+            ::rustmine_server::packet::field::write_nbt(#value, buffer);
+        },
+        Leaf::Json => quote! {
+            ::rustmine_server::packet::field::write_json(#value, buffer);
+        },
+        Leaf::With(path) => quote! {
+            #path::write(#value, buffer);
+        },
+    }
+}
+
+fn read_expression(ty: &Type, attributes: &FieldAttributes, span: Span) -> syn::Result<TokenStream2> {
+    if attributes.remaining {
+        return Ok(quote! {
+            ::rustmine_server::packet::field::read_remaining(&buffer, &mut position)
+        });
+    }
+
+    let mut ty = ty;
+    if attributes.optional {
+        ty = inner_type(ty, "Option", span)?;
+    }
+    if attributes.length_prefixed {
+        ty = inner_type(ty, "Vec", span)?;
+    }
+
+    let mut expression = read_leaf(ty, &attributes.leaf);
+
+    if attributes.length_prefixed {
+        expression = quote! {{
+            let length = <u32 as ::rustmine_server::packet::field::VarIntField>::read_varint(&buffer, &mut position)?;
+            let mut entries = Vec::new(); // Don't trust the length for the allocation
+            for _ in 0..length {
+                entries.push(#expression);
+            }
+            entries
+        }};
+    }
+
+    if attributes.optional {
+        expression = quote! {
+            if <bool as ::rustmine_server::packet::field::PacketField>::read(&buffer, &mut position)? {
+                Some(#expression)
+            } else {
+                None
+            }
+        };
+    }
+
+    Ok(expression)
+}
+
+fn write_statement(
+    ty: &Type,
+    attributes: &FieldAttributes,
+    value: TokenStream2,
+    span: Span,
+) -> syn::Result<TokenStream2> {
+    if attributes.remaining {
+        return Ok(quote! {
+            buffer.extend_from_slice(#value);
+        });
+    }
+
+    // Validate the shape of the field even though the writer doesn't need the inner type.
+    let mut checked = ty;
+    if attributes.optional {
+        checked = inner_type(checked, "Option", span)?;
+    }
+    if attributes.length_prefixed {
+        inner_type(checked, "Vec", span)?;
+    }
+
+    let entry = format_ident!("entry");
+    let present = format_ident!("present");
+
+    let mut statement = write_leaf(
+        &attributes.leaf,
+        if attributes.length_prefixed {
+            quote!(#entry)
+        } else if attributes.optional {
+            quote!(#present)
+        } else {
+            value.clone()
+        },
+    );
+
+    if attributes.length_prefixed {
+        let list = if attributes.optional { quote!(#present) } else { value.clone() };
+        statement = quote! {
+            ::rustmine_server::packet::field::VarIntField::write_varint(&((#list).len() as u32), buffer);
+            for #entry in (#list).iter() {
+                #statement
+            }
+        };
+    }
+
+    if attributes.optional {
+        statement = quote! {
+            match #value {
+                Some(#present) => {
+                    ::rustmine_server::packet::field::PacketField::write(&true, buffer);
+                    #statement
+                }
+                None => ::rustmine_server::packet::field::PacketField::write(&false, buffer),
+            }
+        };
+    }
+
+    Ok(statement)
+}
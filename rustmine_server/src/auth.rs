@@ -0,0 +1,179 @@
+use std::{error::Error, future::Future, net::IpAddr, pin::Pin};
+
+use rsa::{Pkcs1v15Encrypt, RsaPrivateKey, pkcs8::EncodePublicKey};
+use rustmine_lib::game_profile::{GameProfile, GameProfileProperty};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use uuid::Uuid;
+
+pub type SessionVerifierResult = Result<Option<GameProfile>, Box<dyn Error + Send + Sync>>;
+
+/// The RSA key pair used for the encryption handshake, generated once per server.
+pub struct ServerKeyPair {
+    private_key: RsaPrivateKey,
+    public_key_der: Vec<u8>,
+}
+
+impl ServerKeyPair {
+    pub fn generate() -> Result<ServerKeyPair, Box<dyn Error + Send + Sync>> {
+        let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024)?;
+        let public_key_der = private_key.to_public_key().to_public_key_der()?.into_vec();
+
+        Ok(ServerKeyPair { private_key, public_key_der })
+    }
+
+    /// The public key, encoded as an ASN.1 SubjectPublicKeyInfo structure.
+    pub fn public_key_der(&self) -> &[u8] {
+        &self.public_key_der
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, rsa::Error> {
+        self.private_key.decrypt(Pkcs1v15Encrypt, data)
+    }
+}
+
+/// Minecraft's "server hash": a SHA-1 digest formatted as a signed (two's complement) hexadecimal number.
+pub fn server_hash(server_id: &str, shared_secret: &[u8], public_key_der: &[u8]) -> String {
+    let mut digest: [u8; 20] = Sha1::new()
+        .chain_update(server_id.as_bytes())
+        .chain_update(shared_secret)
+        .chain_update(public_key_der)
+        .finalize()
+        .into();
+
+    let negative = digest[0] & 0x80 != 0;
+    if negative {
+        let mut carry = true;
+        for byte in digest.iter_mut().rev() {
+            *byte = !*byte;
+            if carry {
+                (*byte, carry) = byte.overflowing_add(1);
+            }
+        }
+    }
+
+    let hex = digest.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    let hex = hex.trim_start_matches('0');
+
+    match (negative, hex.is_empty()) {
+        (_, true) => "0".to_string(),
+        (true, false) => format!("-{}", hex),
+        (false, false) => hex.to_string(),
+    }
+}
+
+//
+// Asks whoever is responsible for sessions whether the player really joined with the given server hash.
+// Ok(None) means the session is not valid, errors are reserved for failing to get an answer at all.
+//
+
+pub trait SessionVerifier: Send + Sync {
+    fn has_joined<'a>(
+        &'a self,
+        username: &'a str,
+        server_hash: &'a str,
+        ip: Option<IpAddr>,
+    ) -> Pin<Box<dyn Future<Output = SessionVerifierResult> + Send + 'a>>;
+}
+
+pub struct MojangSessionVerifier {
+    pub base_url: String,
+    pub prevent_proxy_connections: bool, // Sends the player's IP along, the session server checks it against the client's
+    client: reqwest::Client,
+}
+
+impl MojangSessionVerifier {
+    /// Points the verifier at another session server, a local stand-in for example.
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            prevent_proxy_connections: false,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl Default for MojangSessionVerifier {
+    fn default() -> Self {
+        Self::with_base_url("https://sessionserver.mojang.com")
+    }
+}
+
+#[derive(Deserialize)]
+struct HasJoinedResponse {
+    id: String,
+    name: String,
+    #[serde(default)]
+    properties: Vec<HasJoinedProperty>,
+}
+
+#[derive(Deserialize)]
+struct HasJoinedProperty {
+    name: String,
+    value: String,
+    signature: Option<String>,
+}
+
+impl SessionVerifier for MojangSessionVerifier {
+    fn has_joined<'a>(
+        &'a self,
+        username: &'a str,
+        server_hash: &'a str,
+        ip: Option<IpAddr>,
+    ) -> Pin<Box<dyn Future<Output = SessionVerifierResult> + Send + 'a>> {
+        Box::pin(async move {
+            let mut query = vec![("username", username.to_string()), ("serverId", server_hash.to_string())];
+            if let (true, Some(ip)) = (self.prevent_proxy_connections, ip) {
+                query.push(("ip", ip.to_string()));
+            }
+
+            let response = self
+                .client
+                .get(format!("{}/session/minecraft/hasJoined", self.base_url.trim_end_matches('/')))
+                .query(&query)
+                .send()
+                .await?
+                .error_for_status()?;
+
+            if response.status() == reqwest::StatusCode::NO_CONTENT {
+                return Ok(None); // Not authenticated
+            }
+
+            let response: HasJoinedResponse = response.json().await?;
+
+            Ok(Some(GameProfile {
+                username: response.name,
+                uuid: Uuid::parse_str(&response.id)?,
+                properties: response
+                    .properties
+                    .into_iter()
+                    .map(|property| GameProfileProperty {
+                        name: property.name,
+                        value: property.value,
+                        signature: property.signature,
+                    })
+                    .collect(),
+            }))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The digests of these names alone, the examples everyone checks their implementation against
+    #[test]
+    fn server_hash_matches_known_vectors() {
+        assert_eq!(server_hash("Notch", &[], &[]), "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48");
+        assert_eq!(server_hash("jeb_", &[], &[]), "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1");
+        assert_eq!(server_hash("simon", &[], &[]), "88e16a1019277b15d58faf0541e11910eb756f6");
+    }
+
+    #[test]
+    fn server_hash_covers_every_part() {
+        let hash = server_hash("", b"secret", b"key");
+        assert_eq!(hash, server_hash("secret", b"key", &[]));
+        assert_ne!(hash, server_hash("", b"secret", b"other key"));
+    }
+}
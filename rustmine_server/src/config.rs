@@ -1,12 +1,53 @@
+use std::{path::PathBuf, time::Duration};
+
+use rustmine_lib::{component::Component, text};
+
+use crate::packet::clientbound::configuration::{ReportDetail, ServerLink};
+
 #[derive(Clone, Debug)]
 pub struct ServerConfig {
     pub bind_address: String,
     pub port: u16,
+    pub online_mode: bool, // Authenticate players against the session server and encrypt the connection
+    pub compression_threshold: Option<u32>, // Packets of at least this many bytes get compressed, None turns compression off
+    pub compression_level: u32, // zlib level, from 0 (fastest) to 9 (smallest)
+    pub keep_alive_interval: Duration, // How often a Keep Alive is sent during Configuration and Play
+    pub keep_alive_timeout: Duration, // How long the client gets to answer before it's disconnected
+    pub forwarding: ForwardingMode,
+    pub accepts_transfers: bool, // Let in clients sent here by another server's Transfer packet
+    pub motd: Component,
+    pub max_players: i32,
+    pub favicon: Option<PathBuf>, // A 64x64 PNG shown in the server list, loaded once when the server starts
+    pub feature_flags: Vec<String>, // Sent to the client during configuration, minecraft:vanilla is needed for anything to work
+    pub server_links: Vec<ServerLink>, // Shown in the pause menu, PlayerConnection::set_server_links overrides them
+    pub report_details: Vec<ReportDetail>, // Added to the client's crash reports, at most 32 of them
+}
+
+// How a proxy in front of the server passes along who is actually connecting
+#[derive(Clone, Debug, Default)]
+pub enum ForwardingMode {
+    #[default]
+    None,
+    Legacy,                     // BungeeCord, appended to the handshake's server address
+    Modern { secret: String },  // Velocity, through a login plugin message signed with the shared secret
 }
 
 impl Default for ServerConfig {
     fn default() -> ServerConfig {
         ServerConfig { bind_address: "0.0.0.0".to_string(),
-                       port: 25565  }
+                       port: 25565,
+                       online_mode: true,
+                       compression_threshold: Some(256),
+                       compression_level: 6,
+                       keep_alive_interval: Duration::from_secs(15),
+                       keep_alive_timeout: Duration::from_secs(30),
+                       forwarding: ForwardingMode::None,
+                       accepts_transfers: false,
+                       motd: text!("A Rustmine Server"),
+                       max_players: 20,
+                       favicon: None,
+                       feature_flags: vec!["minecraft:vanilla".to_string()],
+                       server_links: Vec::new(),
+                       report_details: Vec::new() }
     }
 }
\ No newline at end of file
@@ -0,0 +1,302 @@
+use std::{
+    io::{Error, ErrorKind},
+    pin::Pin,
+    sync::Arc,
+};
+
+use rustmine_lib::{common::configuration_state::ConfigKnownPackEntry, component::Component};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::{
+    event::player_events::PlayerConfiguring,
+    packet::{
+        clientbound::configuration::{
+            ConfigSelectKnownPacksPacket, ConfigurationCustomReportDetailsPacket, ConfigurationPluginMessagePacket,
+            ConfigurationServerLinksPacket, FeatureFlagsPacket,
+        },
+        serverbound::configuration::ClientKnownPacksPacket,
+    },
+    player::PlayerConnection,
+    registry_sync,
+};
+
+pub const BRAND_TASK: &str = "rustmine:brand";
+pub const FEATURE_FLAGS_TASK: &str = "rustmine:feature_flags";
+pub const KNOWN_PACKS_TASK: &str = "rustmine:known_packs";
+pub const REGISTRY_DATA_TASK: &str = "rustmine:registry_data";
+pub const SERVER_LINKS_TASK: &str = "rustmine:server_links";
+pub const REPORT_DETAILS_TASK: &str = "rustmine:report_details";
+pub const PLAYER_CONFIGURING_TASK: &str = "rustmine:player_configuring";
+
+pub type TaskFuture<'a> = Pin<Box<dyn Future<Output = Result<(), Box<Error>>> + Send + 'a>>;
+
+//
+// Configuration runs these one after the other and only finishes once the last one is done. A task can send
+// whatever it likes and wait on the client's answers, anything else the client sends in the meantime (its brand,
+// information, keep alives, plugin messages) is handled by read_packet whatever order it arrives in.
+//
+
+pub trait ConfigurationTask: Send + Sync {
+    /// Namespaced, used to place other tasks around this one.
+    fn name(&self) -> &str;
+
+    fn run<'a>(&'a self, cnx: &'a mut PlayerConnection, reconfiguring: bool) -> TaskFuture<'a>;
+}
+
+pub struct ConfigurationTasks {
+    tasks: RwLock<Vec<Arc<dyn ConfigurationTask>>>,
+}
+
+impl Default for ConfigurationTasks {
+    fn default() -> Self {
+        let tasks: Vec<Arc<dyn ConfigurationTask>> = vec![
+            Arc::new(BrandTask),
+            Arc::new(FeatureFlagsTask),
+            Arc::new(KnownPacksTask),
+            Arc::new(RegistryDataTask),
+            Arc::new(ServerLinksTask),
+            Arc::new(ReportDetailsTask),
+            Arc::new(PlayerConfiguringTask),
+        ];
+
+        Self { tasks: RwLock::new(tasks) }
+    }
+}
+
+impl ConfigurationTasks {
+    /// Runs `task` after every other task.
+    pub async fn add(&self, task: impl ConfigurationTask + 'static) -> Result<(), Box<Error>> {
+        let mut tasks = self.tasks.write().await;
+        check_unique(&tasks, task.name())?;
+        tasks.push(Arc::new(task));
+        Ok(())
+    }
+
+    pub async fn insert_before(&self, name: &str, task: impl ConfigurationTask + 'static) -> Result<(), Box<Error>> {
+        let mut tasks = self.tasks.write().await;
+        check_unique(&tasks, task.name())?;
+        let index = position(&tasks, name)?;
+        tasks.insert(index, Arc::new(task));
+        Ok(())
+    }
+
+    pub async fn insert_after(&self, name: &str, task: impl ConfigurationTask + 'static) -> Result<(), Box<Error>> {
+        let mut tasks = self.tasks.write().await;
+        check_unique(&tasks, task.name())?;
+        let index = position(&tasks, name)?;
+        tasks.insert(index + 1, Arc::new(task));
+        Ok(())
+    }
+
+    /// Returns false if there was no task called `name`. Removing the built-in tasks leaves the client without
+    /// what it needs to join, only do so to replace them.
+    pub async fn remove(&self, name: &str) -> bool {
+        let mut tasks = self.tasks.write().await;
+        let before = tasks.len();
+        tasks.retain(|task| task.name() != name);
+        tasks.len() != before
+    }
+
+    /// The name of every task, in the order they run.
+    pub async fn names(&self) -> Vec<String> {
+        self.tasks.read().await.iter().map(|task| task.name().to_string()).collect()
+    }
+
+    pub(crate) async fn tasks(&self) -> Vec<Arc<dyn ConfigurationTask>> {
+        self.tasks.read().await.clone()
+    }
+}
+
+fn check_unique(tasks: &[Arc<dyn ConfigurationTask>], name: &str) -> Result<(), Box<Error>> {
+    if tasks.iter().any(|task| task.name() == name) {
+        return Err(Box::new(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("There already is a configuration task called {}", name),
+        )));
+    }
+    Ok(())
+}
+
+fn position(tasks: &[Arc<dyn ConfigurationTask>], name: &str) -> Result<usize, Box<Error>> {
+    tasks.iter().position(|task| task.name() == name).ok_or_else(|| {
+        Box::new(Error::new(
+            ErrorKind::NotFound,
+            format!("There is no configuration task called {}", name),
+        ))
+    })
+}
+
+//
+// Built-in tasks
+//
+
+struct BrandTask;
+
+impl ConfigurationTask for BrandTask {
+    fn name(&self) -> &str {
+        BRAND_TASK
+    }
+
+    // The registered plugin channels go along with it
+    fn run<'a>(&'a self, cnx: &'a mut PlayerConnection, _reconfiguring: bool) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let brand_name = cnx.server.lock().await.brand_name.to_owned();
+
+            cnx.write_packet(&ConfigurationPluginMessagePacket::brand_packet(brand_name)).await?;
+            cnx.sync_plugin_channels().await
+        })
+    }
+}
+
+struct FeatureFlagsTask;
+
+impl ConfigurationTask for FeatureFlagsTask {
+    fn name(&self) -> &str {
+        FEATURE_FLAGS_TASK
+    }
+
+    fn run<'a>(&'a self, cnx: &'a mut PlayerConnection, _reconfiguring: bool) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let features = cnx.server.lock().await.config.feature_flags.clone();
+            cnx.write_packet(&FeatureFlagsPacket { features }).await
+        })
+    }
+}
+
+struct KnownPacksTask;
+
+impl ConfigurationTask for KnownPacksTask {
+    fn name(&self) -> &str {
+        KNOWN_PACKS_TASK
+    }
+
+    fn run<'a>(&'a self, cnx: &'a mut PlayerConnection, _reconfiguring: bool) -> TaskFuture<'a> {
+        Box::pin(async move {
+            cnx.write_packet(&ConfigSelectKnownPacksPacket {
+                entries: vec![ConfigKnownPackEntry::minecraft_core()],
+            })
+            .await?;
+
+            let client_known_packs = cnx.wait_for_packet::<ClientKnownPacksPacket>().await?;
+            cnx.set_known_packs(client_known_packs.known_packs.clone()).await;
+            Ok(())
+        })
+    }
+}
+
+struct RegistryDataTask;
+
+impl ConfigurationTask for RegistryDataTask {
+    fn name(&self) -> &str {
+        REGISTRY_DATA_TASK
+    }
+
+    fn run<'a>(&'a self, cnx: &'a mut PlayerConnection, _reconfiguring: bool) -> TaskFuture<'a> {
+        Box::pin(async move {
+            // Without minecraft:core every vanilla entry has to be sent in full
+            let knows_core = cnx
+                .known_packs()
+                .await
+                .contains(&ConfigKnownPackEntry::minecraft_core());
+
+            let (registries, tags) = {
+                let server = cnx.server.lock().await;
+                (registry_sync::registry_data(&server, knows_core), registry_sync::update_tags(&server))
+            };
+
+            for registry in registries {
+                cnx.write_packet(&registry).await?;
+            }
+            cnx.write_packet(&tags).await
+        })
+    }
+}
+
+struct ServerLinksTask;
+
+impl ConfigurationTask for ServerLinksTask {
+    fn name(&self) -> &str {
+        SERVER_LINKS_TASK
+    }
+
+    fn run<'a>(&'a self, cnx: &'a mut PlayerConnection, _reconfiguring: bool) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let links = cnx.server_links().await;
+            if links.is_empty() {
+                return Ok(());
+            }
+            cnx.write_packet(&ConfigurationServerLinksPacket { links }).await
+        })
+    }
+}
+
+struct ReportDetailsTask;
+
+impl ConfigurationTask for ReportDetailsTask {
+    fn name(&self) -> &str {
+        REPORT_DETAILS_TASK
+    }
+
+    fn run<'a>(&'a self, cnx: &'a mut PlayerConnection, _reconfiguring: bool) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let details = cnx.report_details().await;
+            if details.is_empty() {
+                return Ok(());
+            }
+            cnx.write_packet(&ConfigurationCustomReportDetailsPacket { details }).await
+        })
+    }
+}
+
+// Fires PlayerConfiguring for plugins that would rather listen than add a task
+struct PlayerConfiguringTask;
+
+impl ConfigurationTask for PlayerConfiguringTask {
+    fn name(&self) -> &str {
+        PLAYER_CONFIGURING_TASK
+    }
+
+    fn run<'a>(&'a self, cnx: &'a mut PlayerConnection, reconfiguring: bool) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let event_bus = cnx.server.lock().await.event_bus.clone();
+            event_bus
+                .dispatch(&Arc::new(PlayerConfiguring {
+                    player_connection: Mutex::new(cnx.clone()),
+                    reconfiguring,
+                }))
+                .await;
+            Ok(())
+        })
+    }
+}
+
+/// Pushes a resource pack and holds configuration back until the client is done with it. Not part of the default
+/// tasks, add one per pack the server needs.
+pub struct ResourcePackTask {
+    pub name: String,
+    pub url: String,
+    pub hash: String, // SHA-1 as hex, empty to skip the check
+    pub forced: bool,
+    pub prompt: Component,
+}
+
+impl ConfigurationTask for ResourcePackTask {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    // A reconfiguring client still has the pack loaded
+    fn run<'a>(&'a self, cnx: &'a mut PlayerConnection, reconfiguring: bool) -> TaskFuture<'a> {
+        Box::pin(async move {
+            if reconfiguring {
+                return Ok(());
+            }
+
+            let id = cnx
+                .push_resource_pack(&self.url, &self.hash, self.forced, self.prompt.clone())
+                .await?;
+            cnx.wait_for_resource_pack(id).await?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,60 @@
+use std::{any::Any, collections::HashMap};
+
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::sync::oneshot;
+
+use crate::packet::serverbound::{
+    configuration::ConfigurationCookieResponsePacket, login::LoginCookieResponsePacket,
+    play::PlayCookieResponsePacket,
+};
+
+pub const MAX_COOKIE_SIZE: usize = 5120; // The client won't store or send back anything larger
+
+//
+// Cookies are stored by the client under a key and survive transfers to other servers, which makes them the way
+// to carry session data across a hop. The client can send back anything it likes, so sign what needs to be trusted.
+// Typed cookies are stored as JSON.
+//
+
+pub trait Cookie: Serialize + DeserializeOwned {
+    const KEY: &'static str;
+}
+
+// Requests waiting on an answer, several can wait on the same key
+#[derive(Default)]
+pub(crate) struct PendingCookies {
+    requests: HashMap<String, Vec<oneshot::Sender<Option<Vec<u8>>>>>,
+}
+
+impl PendingCookies {
+    pub(crate) fn wait_for(&mut self, key: &str) -> oneshot::Receiver<Option<Vec<u8>>> {
+        let (sender, receiver) = oneshot::channel();
+        self.requests.entry(key.to_string()).or_default().push(sender);
+        receiver
+    }
+
+    // Returns false when nobody asked for this cookie
+    pub(crate) fn resolve(&mut self, key: &str, payload: Option<&Vec<u8>>) -> bool {
+        let Some(waiting) = self.requests.remove(key) else {
+            return false;
+        };
+
+        for sender in waiting {
+            let _ = sender.send(payload.cloned());
+        }
+        true
+    }
+}
+
+// The key and payload of a Cookie Response, whichever state it was sent in
+pub(crate) fn cookie_response(packet: &dyn Any) -> Option<(&str, Option<&Vec<u8>>)> {
+    if let Some(p) = packet.downcast_ref::<LoginCookieResponsePacket>() {
+        return Some((&p.key, p.payload.as_ref()));
+    }
+    if let Some(p) = packet.downcast_ref::<ConfigurationCookieResponsePacket>() {
+        return Some((&p.key, p.payload.as_ref()));
+    }
+    packet
+        .downcast_ref::<PlayCookieResponsePacket>()
+        .map(|p| (p.key.as_str(), p.payload.as_ref()))
+}
@@ -1,14 +1,31 @@
 use std::sync::Arc;
 
+use rustmine_lib::component::Component;
 use tokio::sync::Mutex;
 
-use crate::{packet::Packet, player::{Player, PlayerConnection}, Shared};
+use uuid::Uuid;
+
+use crate::{
+    packet::Packet,
+    player::{Player, PlayerConnection},
+    plugin_channel::PluginMessage,
+    resource_pack::ResourcePackResult,
+    Shared,
+};
 
 pub struct PlayerJoinedServer {
     pub player: Shared<Player>,
 }
 impl super::Event<()> for PlayerJoinedServer {}
 
+// Fired at the end of every configuration, the place to push resource packs and the like before the player
+// (re-)enters Play. `reconfiguring` is set when the player came back from Play through PlayerConnection::reconfigure.
+pub struct PlayerConfiguring {
+    pub player_connection: Mutex<PlayerConnection>,
+    pub reconfiguring: bool,
+}
+impl super::Event<()> for PlayerConfiguring {}
+
 pub struct PlayerSentPacket<P> where P: Packet + Send + Sync + ?Sized {
     pub packet: Arc<P>,
     pub player_connection: Mutex<PlayerConnection>,
@@ -16,4 +33,32 @@ pub struct PlayerSentPacket<P> where P: Packet + Send + Sync + ?Sized {
 
 impl<P> super::Event<()> for PlayerSentPacket<P> where P: Packet + Send + Sync + ?Sized {}
 
+#[derive(Clone, Debug)]
+pub enum DisconnectCause {
+    Kicked,         // PlayerConnection::disconnect was called
+    TimedOut,       // No answer to a keep alive in time
+    ConnectionLost, // The client closed the connection or it dropped
+    Error(String),  // Something went wrong handling the connection
+}
 
+pub struct PlayerDisconnected {
+    pub player_connection: Mutex<PlayerConnection>,
+    pub reason: Option<Component>, // What the client was shown, None if it was never told
+    pub cause: DisconnectCause,
+}
+impl super::Event<()> for PlayerDisconnected {}
+
+// Every answer the client gives about a pack pushed with PlayerConnection::push_resource_pack
+pub struct ResourcePackStatus {
+    pub player_connection: Mutex<PlayerConnection>,
+    pub id: Uuid,
+    pub result: ResourcePackResult,
+}
+impl super::Event<()> for ResourcePackStatus {}
+
+// A message on a channel registered with ChannelRegistry::register, in Configuration or Play
+pub struct PluginMessageReceived<M: PluginMessage> {
+    pub message: M,
+    pub player_connection: Mutex<PlayerConnection>,
+}
+impl<M: PluginMessage> super::Event<()> for PluginMessageReceived<M> {}
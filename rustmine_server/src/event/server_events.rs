@@ -1,7 +1,22 @@
-use crate::{RustmineServer, Shared};
+use std::{net::SocketAddr, sync::Arc};
+
+use crate::{
+    packet::{clientbound::status::StatusResponse, serverbound::handshake::HandshakePacket},
+    RustmineServer, Shared,
+};
 
 pub struct ServerConfigurationStartEvent {
     pub server: Shared<RustmineServer>,
 }
 
-impl super::Event<()> for ServerConfigurationStartEvent {}
\ No newline at end of file
+impl super::Event<()> for ServerConfigurationStartEvent {}
+
+// Fired for every status request, return a modified `response` to change what this client sees.
+// The handshake tells which address was used to connect, e.g. to show a different MOTD per domain.
+pub struct ServerListPing {
+    pub handshake: Arc<HandshakePacket>,
+    pub address: Option<SocketAddr>,
+    pub response: StatusResponse, // The built-in response from RustmineServer::status_response
+}
+
+impl super::Event<StatusResponse> for ServerListPing {}
@@ -0,0 +1,205 @@
+use std::{
+    io::{Error, ErrorKind},
+    net::IpAddr,
+};
+
+use hmac::{Hmac, Mac};
+use rustmine_lib::{
+    data,
+    game_profile::{GameProfile, GameProfileProperty},
+};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::packet::field::PacketField;
+
+pub const VELOCITY_CHANNEL: &str = "velocity:player_info";
+pub const MODERN_FORWARDING_VERSION: u8 = 1; // The only version we can read, it is also the one every Velocity supports
+
+pub struct ForwardedPlayer {
+    pub address: IpAddr,
+    pub profile: GameProfile,
+}
+
+//
+// BungeeCord legacy forwarding: the proxy replaces the handshake's server address with
+// "host\0ip\0uuid\0properties", the properties being the profile's JSON array and possibly left out.
+//
+
+pub fn parse_legacy(server_address: &str, username: &str) -> Result<ForwardedPlayer, Error> {
+    let parts = server_address.split('\0').collect::<Vec<_>>();
+    if parts.len() < 3 {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            "If you wish to use IP forwarding, please enable it in your BungeeCord config as well!",
+        ));
+    }
+
+    let address = parts[1]
+        .parse::<IpAddr>()
+        .map_err(|_| Error::new(ErrorKind::InvalidData, "Invalid forwarded address"))?;
+    let uuid = Uuid::parse_str(parts[2])
+        .map_err(|_| Error::new(ErrorKind::InvalidData, "Invalid forwarded UUID"))?;
+
+    let properties = match parts.get(3) {
+        Some(json) => serde_json::from_str::<Vec<GameProfileProperty>>(json)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("Invalid forwarded properties: {}", e)))?,
+        None => vec![],
+    };
+
+    Ok(ForwardedPlayer {
+        address,
+        profile: GameProfile {
+            username: username.to_string(),
+            uuid,
+            properties,
+        },
+    })
+}
+
+//
+// Velocity modern forwarding: the answer to our velocity:player_info login plugin request.
+// The first 32 bytes are an HMAC-SHA256 of the rest keyed with the forwarding secret, the rest holds the version,
+// address and profile.
+//
+
+pub fn parse_modern(secret: &[u8], data: &[u8]) -> Result<ForwardedPlayer, Error> {
+    if data.len() < 32 {
+        return Err(Error::new(ErrorKind::InvalidData, "Forwarding data is too short"));
+    }
+
+    let (signature, payload) = data.split_at(32);
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+        .map_err(|_| Error::new(ErrorKind::InvalidInput, "Invalid forwarding secret"))?;
+    mac.update(payload);
+    mac.verify_slice(signature).map_err(|_| {
+        Error::new(ErrorKind::PermissionDenied, "Unable to verify player details")
+    })?;
+
+    let mut position = 0;
+    let version = data::read_varint(payload, &mut position)?;
+    if version != MODERN_FORWARDING_VERSION as u32 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Unsupported forwarding version: {}", version),
+        ));
+    }
+
+    let address = data::read_string(payload, &mut position)?
+        .parse::<IpAddr>()
+        .map_err(|_| Error::new(ErrorKind::InvalidData, "Invalid forwarded address"))?;
+    let uuid = data::read_uuid(payload, &mut position)?;
+    let username = data::read_string(payload, &mut position)?;
+
+    let count = data::read_varint(payload, &mut position)?;
+    let properties = (0..count)
+        .map(|_| GameProfileProperty::read(payload, &mut position))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ForwardedPlayer {
+        address,
+        profile: GameProfile {
+            username,
+            uuid,
+            properties,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"hunter2";
+
+    fn modern_payload(version: u32) -> Vec<u8> {
+        let mut payload = vec![];
+        data::write_varint(&mut payload, version);
+        data::write_string(&mut payload, "5.6.7.8");
+        data::write_uuid(&mut payload, &Uuid::from_u128(7));
+        data::write_string(&mut payload, "Alex");
+        data::write_varint(&mut payload, 1);
+        data::write_string(&mut payload, "textures");
+        data::write_string(&mut payload, "value");
+        data::write_bool(&mut payload, true);
+        data::write_string(&mut payload, "signature");
+        payload
+    }
+
+    fn sign(secret: &[u8], payload: Vec<u8>) -> Vec<u8> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(&payload);
+        let mut data = mac.finalize().into_bytes().to_vec();
+        data.extend(payload);
+        data
+    }
+
+    #[test]
+    fn parses_modern_forwarding() {
+        let player = parse_modern(SECRET, &sign(SECRET, modern_payload(1))).unwrap();
+
+        assert_eq!(player.address, "5.6.7.8".parse::<IpAddr>().unwrap());
+        assert_eq!(player.profile.uuid, Uuid::from_u128(7));
+        assert_eq!(player.profile.username, "Alex");
+        assert_eq!(player.profile.properties.len(), 1);
+        assert_eq!(player.profile.properties[0].name, "textures");
+        assert_eq!(player.profile.properties[0].value, "value");
+        assert_eq!(player.profile.properties[0].signature.as_deref(), Some("signature"));
+    }
+
+    #[test]
+    fn rejects_modern_forwarding_with_a_bad_signature() {
+        let error = parse_modern(SECRET, &sign(b"wrong", modern_payload(1))).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+
+        let mut tampered = sign(SECRET, modern_payload(1));
+        *tampered.last_mut().unwrap() ^= 1;
+        let error = parse_modern(SECRET, &tampered).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+
+        let error = parse_modern(SECRET, &[0; 31]).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_unsupported_modern_forwarding_versions() {
+        let error = parse_modern(SECRET, &sign(SECRET, modern_payload(2))).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "Unsupported forwarding version: 2");
+    }
+
+    #[test]
+    fn parses_legacy_forwarding() {
+        let properties = r#"[{"name":"textures","value":"value","signature":"signature"}]"#;
+        let address = ["localhost", "1.2.3.4", "069a79f444e94726a5befca90e38aaf5", properties].join("\0");
+        let player = parse_legacy(&address, "Steve").unwrap();
+
+        assert_eq!(player.address, "1.2.3.4".parse::<IpAddr>().unwrap());
+        assert_eq!(player.profile.uuid.to_string(), "069a79f4-44e9-4726-a5be-fca90e38aaf5");
+        assert_eq!(player.profile.username, "Steve");
+        assert_eq!(player.profile.properties.len(), 1);
+        assert_eq!(player.profile.properties[0].signature.as_deref(), Some("signature"));
+
+        // The properties can be left out
+        let address = ["localhost", "::1", "069a79f4-44e9-4726-a5be-fca90e38aaf5"].join("\0");
+        let player = parse_legacy(&address, "Steve").unwrap();
+        assert_eq!(player.address, "::1".parse::<IpAddr>().unwrap());
+        assert!(player.profile.properties.is_empty());
+    }
+
+    #[test]
+    fn rejects_legacy_forwarding_with_a_bad_uuid() {
+        let error = parse_legacy("localhost\x001.2.3.4\x00not-a-uuid", "Steve").err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "Invalid forwarded UUID");
+    }
+
+    #[test]
+    fn rejects_legacy_forwarding_with_missing_fields() {
+        for address in ["localhost", "localhost\x001.2.3.4"] {
+            let error = parse_legacy(address, "Steve").err().unwrap();
+            assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    io::{Error, ErrorKind},
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+use rustmine_lib::component::{Component, Style, TranslationComponent};
+
+use crate::{
+    event::player_events::DisconnectCause,
+    packet::clientbound::{configuration::ConfigurationKeepAlivePacket, play::PlayKeepAlivePacket},
+    player::{PlayerConnection, State},
+};
+
+#[derive(Default)]
+pub(crate) struct KeepAlive {
+    pending: Option<(u64, Instant)>, // The id we're waiting on, and when it was sent
+    last_sent: Option<Instant>,
+    latency: Option<Duration>,
+    paused: bool,
+}
+
+impl KeepAlive {
+    pub(crate) fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+
+    // Nothing goes out while paused, used around state changes so the client never gets a packet for the wrong state
+    pub(crate) fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub(crate) fn acknowledge(&mut self, keep_alive_id: u64) -> Result<(), Error> {
+        match self.pending {
+            Some((id, sent_at)) if id == keep_alive_id => {
+                let round_trip = sent_at.elapsed();
+
+                // Smoothed the same way as vanilla, so a single slow answer doesn't make the latency jump around
+                self.latency = Some(match self.latency {
+                    Some(latency) => (latency * 3 + round_trip) / 4,
+                    None => round_trip,
+                });
+                self.pending = None;
+                Ok(())
+            }
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unexpected keep alive id: {}", keep_alive_id),
+            )),
+        }
+    }
+}
+
+//
+// Runs alongside the connection for as long as it is open. Each interval a Keep Alive with a random id goes out,
+// unless the previous one hasn't been answered yet. A client that stays silent past the timeout gets disconnected.
+//
+
+pub(crate) async fn keep_alive_loop(mut cnx: PlayerConnection) {
+    let (interval, timeout) = {
+        let server = cnx.server.lock().await;
+        (server.config.keep_alive_interval, server.config.keep_alive_timeout)
+    };
+
+    let mut ticker = tokio::time::interval(interval.min(timeout));
+    let closed = cnx.closed();
+
+    loop {
+        tokio::select! {
+            _ = closed.cancelled() => break,
+            _ = ticker.tick() => {}
+        }
+
+        match send_keep_alive(&mut cnx, interval, timeout).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::TimedOut => {
+                let reason = Component::Translation(TranslationComponent {
+                    translate: "disconnect.timeout".to_string(),
+                    fallback: Some("Timed out".to_string()),
+                    with: None,
+                    style: Style::default(),
+                    extra: vec![],
+                });
+                cnx.disconnect_with(Some(reason), DisconnectCause::TimedOut).await;
+                break;
+            }
+            Err(e) => {
+                cnx.disconnect_after_error(&e).await;
+                break;
+            }
+        }
+    }
+}
+
+async fn send_keep_alive(
+    cnx: &mut PlayerConnection,
+    interval: Duration,
+    timeout: Duration,
+) -> Result<(), Box<std::io::Error>> {
+    let keep_alive = cnx.keep_alive.clone();
+    let mut keep_alive = keep_alive.lock().await; // Held while sending so pausing waits for it
+
+    match keep_alive.pending {
+        Some((_, sent_at)) if sent_at.elapsed() >= timeout => {
+            return Err(Box::new(Error::new(ErrorKind::TimedOut, "Timed out")));
+        }
+        Some(_) => return Ok(()),
+        None => {}
+    }
+
+    let due = keep_alive.last_sent.is_none_or(|sent_at| sent_at.elapsed() >= interval);
+    if keep_alive.paused || !due {
+        return Ok(());
+    }
+
+    let keep_alive_id = rand::thread_rng().r#gen::<u64>();
+    match cnx.state().await {
+        State::Configuration => cnx.write_packet(&ConfigurationKeepAlivePacket { keep_alive_id }).await?,
+        State::Play => cnx.write_packet(&PlayKeepAlivePacket { keep_alive_id }).await?,
+        _ => return Ok(()),
+    }
+
+    let now = Instant::now();
+    keep_alive.pending = Some((keep_alive_id, now));
+    keep_alive.last_sent = Some(now);
+    Ok(())
+}
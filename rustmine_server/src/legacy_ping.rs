@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time,
+};
+
+use crate::packet::clientbound::status::StatusResponse;
+
+pub const LEGACY_PING: u8 = 0xFE; // Can't start a modern handshake, its length would be far too long
+const LEGACY_KICK: u8 = 0xFF;
+
+//
+// Server list ping from before 1.7, still sent by old launchers and monitoring tools.
+// Beta 1.8 to 1.3 only send 0xFE, 1.4 and up follow it with 0x01 (and 1.6 with a plugin message after that).
+// Either way the answer is a kick packet with the status packed into a UTF-16 string.
+//
+
+pub(crate) async fn respond(mut stream: TcpStream, status: StatusResponse) -> Result<(), std::io::Error> {
+    let mut request = [0u8; 256];
+    let mut read = 0;
+
+    // Whatever the client sends after 0xFE isn't needed, but it tells the versions apart
+    while read < request.len() {
+        match time::timeout(Duration::from_millis(100), stream.read(&mut request[read..])).await {
+            Ok(Ok(0)) | Err(_) => break,
+            Ok(Ok(n)) => read += n,
+            Ok(Err(e)) => return Err(e),
+        }
+
+        if read >= 2 && request[1] != 0x01 {
+            break;
+        }
+    }
+
+    stream.write_all(&encode_response(&request[..read], &status)).await?;
+    stream.shutdown().await
+}
+
+// The kick packet answering `request`, which is everything the client sent
+fn encode_response(request: &[u8], status: &StatusResponse) -> Vec<u8> {
+    let motd = status.description.plain_text();
+    let response = if request.len() >= 2 && request[1] == 0x01 {
+        format!(
+            "§1\0{}\0{}\0{}\0{}\0{}",
+            status.version.protocol,
+            status.version.name.replace('\0', ""),
+            motd.replace('\0', ""),
+            status.players.online,
+            status.players.max,
+        )
+    } else {
+        format!(
+            "{}§{}§{}",
+            motd.replace('§', ""),
+            status.players.online,
+            status.players.max,
+        )
+    };
+
+    let encoded = response.encode_utf16().collect::<Vec<_>>();
+
+    let mut buffer = Vec::with_capacity(3 + encoded.len() * 2);
+    buffer.push(LEGACY_KICK);
+    buffer.extend_from_slice(&(encoded.len() as u16).to_be_bytes()); // Length in characters, not bytes
+    for unit in encoded {
+        buffer.extend_from_slice(&unit.to_be_bytes());
+    }
+
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use rustmine_lib::text;
+
+    use super::*;
+    use crate::packet::clientbound::status::{StatusPlayers, StatusVersion};
+
+    fn status() -> StatusResponse {
+        StatusResponse {
+            version: StatusVersion {
+                name: "1.21.6".to_string(),
+                protocol: 771,
+            },
+            players: StatusPlayers {
+                max: 20,
+                online: 3,
+                sample: vec![],
+            },
+            description: text!("A §Server"),
+            favicon: None,
+            enforces_secure_chat: false,
+        }
+    }
+
+    // The kick packet a client expects for `message`
+    fn kick(message: &str) -> Vec<u8> {
+        let units = message.encode_utf16().collect::<Vec<_>>();
+        let mut kick = vec![LEGACY_KICK];
+        kick.extend_from_slice(&(units.len() as u16).to_be_bytes());
+        for unit in units {
+            kick.extend_from_slice(&unit.to_be_bytes());
+        }
+        kick
+    }
+
+    #[test]
+    fn answers_beta_pings_with_the_old_format() {
+        // Section signs would be taken for separators
+        assert_eq!(encode_response(&[LEGACY_PING], &status()), kick("A Server§3§20"));
+    }
+
+    #[test]
+    fn answers_1_4_and_1_5_pings_with_the_new_format() {
+        assert_eq!(
+            encode_response(&[LEGACY_PING, 0x01], &status()),
+            kick(&["§1", "771", "1.21.6", "A §Server", "3", "20"].join("\0")),
+        );
+    }
+
+    #[test]
+    fn answers_1_6_pings_with_the_new_format() {
+        // 0xFA is followed by the MC|PingHost plugin message, it's ignored
+        let mut request = vec![LEGACY_PING, 0x01, 0xFA, 0x00, 0x0B];
+        request.extend("MC|PingHost".encode_utf16().flat_map(|unit| unit.to_be_bytes()));
+        request.extend_from_slice(&[0x00, 0x07, 0x4A]);
+
+        assert_eq!(
+            encode_response(&request, &status()),
+            kick(&["§1", "771", "1.21.6", "A §Server", "3", "20"].join("\0")),
+        );
+    }
+
+    #[test]
+    fn encodes_the_length_in_characters() {
+        let response = encode_response(&[LEGACY_PING], &status());
+
+        assert_eq!(&response[..3], &[0xFF, 0x00, 0x0D]);
+        assert_eq!(&response[3..7], &[0x00, b'A', 0x00, b' ']);
+        assert_eq!(response.len(), 3 + 13 * 2);
+    }
+}
@@ -1,28 +1,55 @@
+extern crate self as rustmine_server; // Lets #[derive(Packet)] refer to this crate by name from within it
+
 pub const PROTOCOL_VERSION: i32 = 771;
 
 pub type Shared<T> = Arc<Mutex<T>>; // Move this elsewhere maybe?
 
+pub mod auth;
 pub mod config;
+pub mod configuration_task;
+pub mod cookie;
 pub mod event;
+pub mod forwarding;
+mod keep_alive;
+mod legacy_ping;
 pub mod packet;
 pub mod player;
+pub mod plugin_channel;
+mod registry_sync;
+pub mod resource_pack;
+pub mod status;
 pub mod world;
 
-use std::sync::Arc;
-use rustmine_lib::dimension;
+use std::sync::{Arc, OnceLock};
+use rustmine_lib::{biome_registry::BiomeRegistry, dimension, tags};
 use tokio::{net::TcpListener, sync::Mutex, task};
 
 use crate::{
-    config::ServerConfig, event::{server_events::ServerConfigurationStartEvent, EventBus}, packet::serverbound::handshake::HandshakePacket,
+    auth::{MojangSessionVerifier, ServerKeyPair, SessionVerifier},
+    config::ServerConfig, event::{server_events::ServerConfigurationStartEvent, EventBus}, packet::{
+        clientbound::status::{StatusPlayers, StatusResponse, StatusVersion},
+        registry::PacketRegistry,
+        serverbound::handshake::HandshakePacket,
+    },
     player::PlayerConnection,
+    status::OnlinePlayer,
 };
 
 pub struct RustmineServer {
     pub brand_name: String,
     pub config: ServerConfig,
     pub event_bus: Arc<EventBus>,
+    pub packet_registry: Arc<PacketRegistry>,
+    pub channel_registry: Arc<plugin_channel::ChannelRegistry>, // Plugin message channels announced to every client
+    pub configuration_tasks: Arc<configuration_task::ConfigurationTasks>, // Run in order for every player before Play
     pub dimension_type_manager: dimension::DimensionTypeManager,
+    pub biome_registry: Arc<BiomeRegistry>, // Synced as minecraft:worldgen/biome, chunks refer to biomes by their id in it
+    pub tag_registry: tags::TagRegistry, // Sent to every client during configuration, add custom tags here
     pub world_manager: world::WorldManager,
+    pub session_verifier: Arc<dyn SessionVerifier>, // Swap this out to authenticate against something other than Mojang
+    key_pair: OnceLock<Arc<ServerKeyPair>>,
+    online_players: Vec<OnlinePlayer>, // Everyone that made it to Play
+    favicon: Option<String>, // The data URI loaded from config.favicon
 }
 
 impl RustmineServer {
@@ -30,26 +57,102 @@ impl RustmineServer {
         Arc::new(Mutex::new(RustmineServer {
             config,
             event_bus: Arc::new(EventBus::default()),
+            packet_registry: Arc::new(PacketRegistry::default()),
+            channel_registry: Arc::new(plugin_channel::ChannelRegistry::default()),
+            configuration_tasks: Arc::new(configuration_task::ConfigurationTasks::default()),
             dimension_type_manager: dimension::DimensionTypeManager::default(),
+            biome_registry: Arc::new(BiomeRegistry::default()),
+            tag_registry: tags::TagRegistry::vanilla(),
             world_manager: world::WorldManager::default(),
             brand_name: "Rustmine".to_owned(),
+            session_verifier: Arc::new(MojangSessionVerifier::default()),
+            key_pair: OnceLock::new(),
+            online_players: Vec::new(),
+            favicon: None,
         }))
     }
 
-    pub async fn run(server: Shared<RustmineServer>) -> Result<(), Box<std::io::Error>> {
-        let server_lock = server.lock().await;
+    /// What the server list shows, built from the config and the players currently online.
+    pub fn status_response(&self) -> StatusResponse {
+        StatusResponse {
+            version: StatusVersion::default(),
+            players: StatusPlayers {
+                max: self.config.max_players,
+                online: self.online_players.len() as i32,
+                sample: status::sample_players(&self.online_players),
+            },
+            description: self.config.motd.clone(),
+            favicon: self.favicon.clone(),
+            enforces_secure_chat: false,
+        }
+    }
+
+    pub fn online_players(&self) -> &[OnlinePlayer] {
+        &self.online_players
+    }
+
+    pub(crate) fn add_online_player(&mut self, player: OnlinePlayer) {
+        self.online_players.push(player);
+    }
+
+    pub(crate) fn remove_online_player(&mut self, uuid: &uuid::Uuid) {
+        if let Some(index) = self.online_players.iter().position(|player| &player.uuid == uuid) {
+            self.online_players.swap_remove(index);
+        }
+    }
 
-        let listener = TcpListener::bind(format!(
-            "{}:{}",
-            server_lock.config.bind_address, server_lock.config.port
-        ))
-        .await?;
+    /// The key pair used to encrypt connections, generated at startup when the server is in online mode.
+    pub fn key_pair(&self) -> Result<Arc<ServerKeyPair>, Box<dyn std::error::Error + Send + Sync>> {
+        self.key_pair
+            .get()
+            .cloned()
+            .ok_or_else(|| "The server has no key pair, online mode was off when it started".into())
+    }
 
-        println!("Server listening on port: {:?}", server_lock.config.port);
+    pub async fn run(server: Shared<RustmineServer>) -> Result<(), Box<std::io::Error>> {
+        let listener = Self::bind(&server).await?;
+        Self::serve(server, listener).await
+    }
 
-        let event_bus = server_lock.event_bus.clone();
-        drop(server_lock);
+    /// Does everything the server needs before it can take connections, then binds to the configured address.
+    pub async fn bind(server: &Shared<RustmineServer>) -> Result<TcpListener, Box<std::io::Error>> {
+        let (favicon, report_details, online_mode, address) = {
+            let server = server.lock().await;
+            let config = &server.config;
+            (
+                config.favicon.clone(),
+                config.report_details.clone(),
+                config.online_mode,
+                format!("{}:{}", config.bind_address, config.port),
+            )
+        };
+
+        let favicon = favicon.map(status::load_favicon).transpose()?;
+        packet::clientbound::configuration::validate_report_details(&report_details)?;
+
+        // Slow enough that it shouldn't hold up anyone else, so it's done before the first player logs in
+        let key_pair = if online_mode {
+            let key_pair = task::spawn_blocking(ServerKeyPair::generate).await.map_err(std::io::Error::other)?;
+            Some(key_pair.map_err(std::io::Error::other)?)
+        } else {
+            None
+        };
+
+        let listener = TcpListener::bind(address).await?;
+        println!("Server listening on port: {:?}", listener.local_addr()?.port());
+
+        let mut server = server.lock().await;
+        server.favicon = favicon;
+        if let Some(key_pair) = key_pair {
+            let _ = server.key_pair.set(Arc::new(key_pair));
+        }
+
+        Ok(listener)
+    }
 
+    /// Accepts connections on `listener` until the server stops, see [`Self::bind`].
+    pub async fn serve(server: Shared<RustmineServer>, listener: TcpListener) -> Result<(), Box<std::io::Error>> {
+        let event_bus = server.lock().await.event_bus.clone();
         event_bus.dispatch(&Arc::new(ServerConfigurationStartEvent {
             server: server.clone()
         })).await;
@@ -60,15 +163,33 @@ impl RustmineServer {
                     let server = Arc::clone(&server);
 
                     task::spawn(async move {
+                        // Legacy pings don't speak the modern protocol at all, so they're caught before any parsing
+                        let mut first_byte = [0u8; 1];
+                        if let Ok(1) = stream.peek(&mut first_byte).await
+                            && first_byte[0] == legacy_ping::LEGACY_PING
+                        {
+                            let status = server.lock().await.status_response();
+                            if let Err(e) = legacy_ping::respond(stream, status).await {
+                                eprintln!("Failed to answer legacy ping from {}: {}", addr, e);
+                            }
+                            return;
+                        }
+
                         let mut connection = PlayerConnection::new(stream, &server);
-                        let packet = connection.read_packet().await.unwrap();
 
-                        let handshake = packet::downcast_packet::<HandshakePacket>(packet).unwrap();
+                        let result = async {
+                            let packet = connection.read_packet().await?;
+                            let handshake = packet::downcast_packet::<HandshakePacket>(packet)?;
 
-                        println!("New connection accepted from: {}", addr);
-                        connection.handle_handshake(handshake).await.unwrap();
+                            println!("New connection accepted from: {}", addr);
+                            connection.handle_handshake(handshake).await
+                        }
+                        .await;
 
-                        ()
+                        match result {
+                            Ok(()) => connection.close(), // Stops the tasks still holding on to the connection
+                            Err(e) => connection.disconnect_after_error(&e).await,
+                        }
                     });
                 }
                 Err(err) => {
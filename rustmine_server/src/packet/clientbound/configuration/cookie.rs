@@ -0,0 +1,15 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x00, state = configuration, direction = clientbound)]
+pub struct ConfigurationCookieRequestPacket {
+    pub key: String,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x0A, state = configuration, direction = clientbound)]
+pub struct ConfigurationStoreCookiePacket {
+    pub key: String,
+    #[packet(length_prefixed)]
+    pub payload: Vec<u8>,
+}
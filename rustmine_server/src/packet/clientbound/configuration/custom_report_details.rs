@@ -0,0 +1,65 @@
+use std::io::{Error, ErrorKind};
+
+use rustmine_lib::data;
+
+use crate::packet::{field::PacketField, Packet};
+
+pub const MAX_REPORT_DETAILS: usize = 32;
+pub const MAX_REPORT_DETAIL_TITLE_LENGTH: usize = 128;
+pub const MAX_REPORT_DETAIL_DESCRIPTION_LENGTH: usize = 4096;
+
+#[derive(Packet)]
+#[packet(id = 0x0F, state = configuration, direction = clientbound)]
+pub struct ConfigurationCustomReportDetailsPacket {
+    #[packet(length_prefixed)]
+    pub details: Vec<ReportDetail>,
+}
+
+/// A key-value pair the client adds to its crash reports and debug reports while connected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReportDetail {
+    pub title: String,
+    pub description: String,
+}
+
+impl ReportDetail {
+    pub fn new(title: impl Into<String>, description: impl Into<String>) -> Self {
+        ReportDetail { title: title.into(), description: description.into() }
+    }
+}
+
+impl PacketField for ReportDetail {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        Ok(ReportDetail {
+            title: data::read_string(buffer, position)?,
+            description: data::read_string(buffer, position)?,
+        })
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_string(buffer, &self.title);
+        data::write_string(buffer, &self.description);
+    }
+}
+
+pub(crate) fn validate_report_details(details: &[ReportDetail]) -> Result<(), Error> {
+    if details.len() > MAX_REPORT_DETAILS {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("The client takes at most {} report details", MAX_REPORT_DETAILS),
+        ));
+    }
+
+    for detail in details {
+        if detail.title.chars().count() > MAX_REPORT_DETAIL_TITLE_LENGTH
+            || detail.description.chars().count() > MAX_REPORT_DETAIL_DESCRIPTION_LENGTH
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Report detail {} is too long", detail.title),
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use rustmine_lib::component::Component;
+
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x02, state = configuration, direction = clientbound)]
+pub struct ConfigurationDisconnectPacket {
+    #[packet(nbt)]
+    pub reason: Component,
+}
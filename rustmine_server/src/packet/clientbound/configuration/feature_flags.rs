@@ -0,0 +1,8 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x0C, state = configuration, direction = clientbound)]
+pub struct FeatureFlagsPacket {
+    #[packet(length_prefixed)]
+    pub features: Vec<String>, // e.g. minecraft:vanilla, minecraft:trade_rebalance
+}
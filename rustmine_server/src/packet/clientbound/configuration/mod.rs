@@ -2,4 +2,34 @@ mod config_plugin_message;
 pub use config_plugin_message::*;
 
 mod select_known_packs;
-pub use select_known_packs::*;
\ No newline at end of file
+pub use select_known_packs::*;
+
+mod keep_alive;
+pub use keep_alive::*;
+
+mod disconnect;
+pub use disconnect::*;
+
+mod cookie;
+pub use cookie::*;
+
+mod transfer;
+pub use transfer::*;
+
+mod registry_data;
+pub use registry_data::*;
+
+mod feature_flags;
+pub use feature_flags::*;
+
+mod update_tags;
+pub use update_tags::*;
+
+mod resource_pack;
+pub use resource_pack::*;
+
+mod custom_report_details;
+pub use custom_report_details::*;
+
+mod server_links;
+pub use server_links::*;
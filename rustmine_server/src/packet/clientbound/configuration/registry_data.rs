@@ -0,0 +1,35 @@
+use std::io::{Error, ErrorKind};
+
+use rustmine_lib::data;
+
+use crate::packet::{field::PacketField, Packet};
+
+#[derive(Packet)]
+#[packet(id = 0x07, state = configuration, direction = clientbound)]
+pub struct RegistryDataPacket {
+    pub registry_id: String,
+    #[packet(length_prefixed)]
+    pub entries: Vec<RegistryDataEntry>,
+}
+
+pub struct RegistryDataEntry {
+    pub id: String,
+    pub data: Option<Vec<u8>>, // Already encoded network NBT, None lets the client take it from a known pack
+}
+
+impl PacketField for RegistryDataEntry {
+    fn read(_buffer: &[u8], _position: &mut usize) -> Result<Self, Error> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "Registry data entries are only ever sent",
+        ))
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_string(buffer, &self.id);
+        data::write_bool(buffer, self.data.is_some());
+        if let Some(nbt) = &self.data {
+            data::write_bytes(buffer, nbt);
+        }
+    }
+}
@@ -1,27 +1,10 @@
-use rustmine_lib::{common::configuration_state::ConfigKnownPackEntry, data};
+use rustmine_lib::common::configuration_state::ConfigKnownPackEntry;
 
-use crate::{clientbound_packet, packet::Packet, packet_id};
+use crate::packet::Packet;
 
+#[derive(Packet)]
+#[packet(id = 0x0E, state = configuration, direction = clientbound)]
 pub struct ConfigSelectKnownPacksPacket {
+    #[packet(length_prefixed)]
     pub entries: Vec<ConfigKnownPackEntry>
 }
-
-impl Packet for ConfigSelectKnownPacksPacket {
-    packet_id!(0x0E);
-    clientbound_packet!();
-    
-    fn write_to(self: &Self, buffer: &mut Vec<u8>) {
-        let entries_length = self.entries.len();
-
-        data::write_varint(buffer, entries_length.try_into().unwrap());
-        for entry in &self.entries {
-            let split_entry_name = entry.name.split_once(":").unwrap();
-
-            data::write_string(buffer, split_entry_name.0);
-            data::write_string(buffer, split_entry_name.1);
-            data::write_string(buffer, &entry.version);
-        }
-
-    }
-}
-
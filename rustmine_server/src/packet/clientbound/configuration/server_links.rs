@@ -0,0 +1,84 @@
+use std::io::{Error, ErrorKind};
+
+use rustmine_lib::{component::Component, data};
+
+use crate::packet::{field::{self, PacketField}, Packet};
+
+#[derive(Packet)]
+#[packet(id = 0x10, state = configuration, direction = clientbound)]
+pub struct ConfigurationServerLinksPacket {
+    #[packet(length_prefixed)]
+    pub links: Vec<ServerLink>,
+}
+
+/// A link shown in the client's pause menu.
+#[derive(Clone, Debug)]
+pub struct ServerLink {
+    pub label: ServerLinkLabel,
+    pub url: String,
+}
+
+impl ServerLink {
+    pub fn new(label: ServerLinkLabel, url: impl Into<String>) -> Self {
+        ServerLink { label, url: url.into() }
+    }
+}
+
+// The built-in labels are translated by the client, anything else needs its own component
+#[derive(Clone, Debug)]
+pub enum ServerLinkLabel {
+    BugReport,
+    CommunityGuidelines,
+    Support,
+    Status,
+    Feedback,
+    Community,
+    Website,
+    Forums,
+    News,
+    Announcements,
+    Custom(Component),
+}
+
+impl PacketField for ServerLinkLabel {
+    fn read(_buffer: &[u8], _position: &mut usize) -> Result<Self, Error> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "Server links are only ever sent",
+        ))
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        let id = match self {
+            ServerLinkLabel::Custom(component) => {
+                data::write_bool(buffer, false);
+                field::write_nbt(component, buffer);
+                return;
+            }
+            ServerLinkLabel::BugReport => 0,
+            ServerLinkLabel::CommunityGuidelines => 1,
+            ServerLinkLabel::Support => 2,
+            ServerLinkLabel::Status => 3,
+            ServerLinkLabel::Feedback => 4,
+            ServerLinkLabel::Community => 5,
+            ServerLinkLabel::Website => 6,
+            ServerLinkLabel::Forums => 7,
+            ServerLinkLabel::News => 8,
+            ServerLinkLabel::Announcements => 9,
+        };
+
+        data::write_bool(buffer, true);
+        data::write_varint(buffer, id);
+    }
+}
+
+impl PacketField for ServerLink {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        Ok(ServerLink { label: ServerLinkLabel::read(buffer, position)?, url: data::read_string(buffer, position)? })
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        self.label.write(buffer);
+        data::write_string(buffer, &self.url);
+    }
+}
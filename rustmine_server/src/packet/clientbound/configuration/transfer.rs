@@ -0,0 +1,9 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x0B, state = configuration, direction = clientbound)]
+pub struct ConfigurationTransferPacket {
+    pub host: String,
+    #[packet(varint)]
+    pub port: u32,
+}
@@ -0,0 +1,62 @@
+use std::io::Error;
+
+use rustmine_lib::data;
+
+use crate::packet::{field::PacketField, Packet};
+
+#[derive(Packet)]
+#[packet(id = 0x0D, state = configuration, direction = clientbound)]
+pub struct UpdateTagsPacket {
+    #[packet(length_prefixed)]
+    pub registries: Vec<RegistryTags>,
+}
+
+pub struct RegistryTags {
+    pub registry: String,
+    pub tags: Vec<Tag>,
+}
+
+pub struct Tag {
+    pub name: String,
+    pub entries: Vec<u32>, // Protocol ids in the registry
+}
+
+impl PacketField for RegistryTags {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        let registry = data::read_string(buffer, position)?;
+        let count = data::read_varint(buffer, position)?;
+        let tags = (0..count)
+            .map(|_| Tag::read(buffer, position))
+            .collect::<Result<_, _>>()?;
+
+        Ok(RegistryTags { registry, tags })
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_string(buffer, &self.registry);
+        data::write_varint(buffer, self.tags.len() as u32);
+        for tag in &self.tags {
+            tag.write(buffer);
+        }
+    }
+}
+
+impl PacketField for Tag {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        let name = data::read_string(buffer, position)?;
+        let count = data::read_varint(buffer, position)?;
+        let entries = (0..count)
+            .map(|_| data::read_varint(buffer, position))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Tag { name, entries })
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_string(buffer, &self.name);
+        data::write_varint(buffer, self.entries.len() as u32);
+        for id in &self.entries {
+            data::write_varint(buffer, *id);
+        }
+    }
+}
@@ -1,23 +1,56 @@
+use rustmine_lib::{component::Component, game_profile::GameProfileProperty};
 use uuid::Uuid;
 
-use crate::{
-    clientbound_packet,
-    packet::{Packet, data},
-    packet_id,
-};
+use crate::packet::Packet;
 
+// Login is the only state where the reason is still sent as JSON text
+#[derive(Packet)]
+#[packet(id = 0x00, state = login, direction = clientbound)]
+pub struct LoginDisconnectPacket {
+    #[packet(json)]
+    pub reason: Component,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x01, state = login, direction = clientbound)]
+pub struct EncryptionRequestPacket {
+    pub server_id: String, // Always empty since 1.7
+    #[packet(length_prefixed)]
+    pub public_key: Vec<u8>,
+    #[packet(length_prefixed)]
+    pub verify_token: Vec<u8>,
+    pub should_authenticate: bool,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x02, state = login, direction = clientbound)]
 pub struct LoginSuccessPacket {
-    pub username: String,
     pub uuid: Uuid,
+    pub username: String,
+    #[packet(length_prefixed)]
+    pub properties: Vec<GameProfileProperty>,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x03, state = login, direction = clientbound)]
+pub struct SetCompressionPacket {
+    #[packet(varint)]
+    pub threshold: i32, // A negative threshold would turn compression off
 }
 
-impl Packet for LoginSuccessPacket {
-    packet_id!(0x02);
-    clientbound_packet!();
+// Custom payload the client is expected to answer, with the same message id
+#[derive(Packet)]
+#[packet(id = 0x04, state = login, direction = clientbound)]
+pub struct LoginPluginRequestPacket {
+    #[packet(varint)]
+    pub message_id: u32,
+    pub channel: String,
+    #[packet(remaining)]
+    pub data: Vec<u8>,
+}
 
-    fn write_to(self: &Self, buffer: &mut Vec<u8>) {
-        data::write_uuid(buffer, &self.uuid);
-        data::write_string(buffer, &self.username);
-        data::write_varint(buffer, 0);
-    }
+#[derive(Packet)]
+#[packet(id = 0x05, state = login, direction = clientbound)]
+pub struct LoginCookieRequestPacket {
+    pub key: String,
 }
@@ -1,17 +1,11 @@
-use crate::{id_match, packet::Packet, packet_id};
+use crate::packet::Packet;
 
 pub mod configuration;
 pub mod login;
+pub mod play;
 pub mod status;
 
+// Also sent back by the client to acknowledge the end of the configuration
+#[derive(Packet)]
+#[packet(id = 0x03, state = configuration, direction = both)]
 pub struct FinishConfigurationPacket;
-
-impl Packet for FinishConfigurationPacket {
-    packet_id!(0x03);
-    fn write_to(self: &Self, _: &mut Vec<u8>) {}
-
-    async fn read_from(id: u32, _: Vec<u8>) -> Result<Box<Self>, Box<std::io::Error>> {
-        id_match!(id, Self::id());
-        Ok(Box::new(FinishConfigurationPacket))
-    }
-}
@@ -0,0 +1,46 @@
+use std::io::Error;
+
+use rustmine_lib::{biome_registry::BiomeRegistry, chunk::Chunk, data};
+
+use crate::packet::{field::PacketField, Packet};
+
+#[derive(Packet)]
+#[packet(id = 0x0D, state = play, direction = clientbound)]
+pub struct ChunkBiomesPacket {
+    #[packet(length_prefixed)]
+    pub chunks: Vec<ChunkBiomeData>,
+}
+
+/// The biomes of every section of a chunk, replacing what the client has for it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkBiomeData {
+    pub x: i32,
+    pub z: i32,
+    pub data: Vec<u8>,
+}
+
+impl ChunkBiomeData {
+    pub fn new(chunk: &Chunk, biomes: &BiomeRegistry) -> Result<Self, Box<Error>> {
+        let mut data = Vec::new();
+        chunk.write_biomes(&mut data, biomes)?;
+        Ok(ChunkBiomeData { x: chunk.x, z: chunk.z, data })
+    }
+}
+
+// The position goes as a single long with Z in the upper half, the same bytes as Z then X
+impl PacketField for ChunkBiomeData {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        let z = data::read_int(buffer, position)?;
+        let x = data::read_int(buffer, position)?;
+        let length = data::read_varint(buffer, position)? as usize;
+
+        Ok(ChunkBiomeData { x, z, data: data::read_bytes(buffer, position, length)? })
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_int(buffer, self.z);
+        data::write_int(buffer, self.x);
+        data::write_varint(buffer, self.data.len() as u32);
+        data::write_bytes(buffer, &self.data);
+    }
+}
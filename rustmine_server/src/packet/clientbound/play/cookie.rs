@@ -0,0 +1,15 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x15, state = play, direction = clientbound)]
+pub struct PlayCookieRequestPacket {
+    pub key: String,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x71, state = play, direction = clientbound)]
+pub struct PlayStoreCookiePacket {
+    pub key: String,
+    #[packet(length_prefixed)]
+    pub payload: Vec<u8>,
+}
@@ -0,0 +1,8 @@
+use crate::packet::{clientbound::configuration::ReportDetail, Packet};
+
+#[derive(Packet)]
+#[packet(id = 0x82, state = play, direction = clientbound)]
+pub struct PlayCustomReportDetailsPacket {
+    #[packet(length_prefixed)]
+    pub details: Vec<ReportDetail>,
+}
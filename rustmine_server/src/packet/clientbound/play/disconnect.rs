@@ -0,0 +1,10 @@
+use rustmine_lib::component::Component;
+
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x1C, state = play, direction = clientbound)]
+pub struct PlayDisconnectPacket {
+    #[packet(nbt)]
+    pub reason: Component,
+}
@@ -0,0 +1,7 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x26, state = play, direction = clientbound)]
+pub struct PlayKeepAlivePacket {
+    pub keep_alive_id: u64,
+}
@@ -0,0 +1,29 @@
+mod keep_alive;
+pub use keep_alive::*;
+
+mod disconnect;
+pub use disconnect::*;
+
+mod cookie;
+pub use cookie::*;
+
+mod transfer;
+pub use transfer::*;
+
+mod resource_pack;
+pub use resource_pack::*;
+
+mod start_configuration;
+pub use start_configuration::*;
+
+mod plugin_message;
+pub use plugin_message::*;
+
+mod custom_report_details;
+pub use custom_report_details::*;
+
+mod server_links;
+pub use server_links::*;
+
+mod chunk_biomes;
+pub use chunk_biomes::*;
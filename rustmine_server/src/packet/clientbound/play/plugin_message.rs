@@ -0,0 +1,9 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x18, state = play, direction = clientbound)]
+pub struct PlayPluginMessagePacket {
+    pub channel: String,
+    #[packet(remaining)]
+    pub data: Vec<u8>,
+}
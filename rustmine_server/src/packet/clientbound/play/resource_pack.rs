@@ -0,0 +1,22 @@
+use rustmine_lib::component::Component;
+use uuid::Uuid;
+
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x49, state = play, direction = clientbound)]
+pub struct PlayRemoveResourcePackPacket {
+    #[packet(optional)]
+    pub id: Option<Uuid>, // None removes every pack
+}
+
+#[derive(Packet)]
+#[packet(id = 0x4A, state = play, direction = clientbound)]
+pub struct PlayAddResourcePackPacket {
+    pub id: Uuid,
+    pub url: String,
+    pub hash: String, // SHA-1 of the pack as 40 hex characters, or empty to skip the check
+    pub forced: bool,
+    #[packet(optional, nbt)]
+    pub prompt: Option<Component>,
+}
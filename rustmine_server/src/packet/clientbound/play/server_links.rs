@@ -0,0 +1,8 @@
+use crate::packet::{clientbound::configuration::ServerLink, Packet};
+
+#[derive(Packet)]
+#[packet(id = 0x83, state = play, direction = clientbound)]
+pub struct PlayServerLinksPacket {
+    #[packet(length_prefixed)]
+    pub links: Vec<ServerLink>,
+}
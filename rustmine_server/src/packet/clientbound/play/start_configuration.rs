@@ -0,0 +1,5 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x6F, state = play, direction = clientbound)]
+pub struct StartConfigurationPacket;
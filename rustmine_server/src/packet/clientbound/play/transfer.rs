@@ -0,0 +1,9 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x7B, state = play, direction = clientbound)]
+pub struct PlayTransferPacket {
+    pub host: String,
+    #[packet(varint)]
+    pub port: u32,
+}
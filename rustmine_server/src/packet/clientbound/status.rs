@@ -1,36 +1,20 @@
 use rustmine_lib::component::Component;
 use serde::{Deserialize, Serialize};
 
-use crate::{
-    clientbound_packet,
-    packet::{Packet, data},
-    packet_id,
-};
+use crate::packet::Packet;
 
+#[derive(Packet)]
+#[packet(id = 0x00, state = status, direction = clientbound)]
 pub struct StatusResponsePacket {
+    #[packet(json)]
     pub response: StatusResponse,
 }
 
-impl Packet for StatusResponsePacket {
-    packet_id!(0x00);
-    clientbound_packet!();
-
-    fn write_to(&self, buffer: &mut Vec<u8>) {
-        data::write_string(buffer, &serde_json::to_string(&self.response).unwrap());
-    }
-}
-
+#[derive(Packet)]
+#[packet(id = 0x01, state = status, direction = clientbound)]
 pub struct StatusPongPacket {
     pub payload: u64,
 }
-impl Packet for StatusPongPacket {
-    packet_id!(0x01);
-    clientbound_packet!();
-
-    fn write_to(&self, buffer: &mut Vec<u8>) {
-        data::write_long(buffer, self.payload);
-    }
-}
 
 impl Default for StatusVersion {
     fn default() -> Self {
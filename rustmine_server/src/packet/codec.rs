@@ -0,0 +1,149 @@
+use std::io::{Error, ErrorKind};
+
+use bytes::{Buf, BytesMut};
+use rustmine_lib::data;
+use tokio_util::codec::{Decoder, Encoder};
+
+// The length prefix is a VarInt of at most 3 bytes, so no frame can ever be longer than this (just under 2 MiB)
+pub const MAX_PACKET_LENGTH: usize = (1 << 21) - 1;
+const MAX_LENGTH_BYTES: usize = 3;
+
+//
+// Splits the byte stream into frames: a VarInt length followed by that many bytes.
+// Frames come out without their length prefix, anything not yet complete stays buffered until the next read.
+//
+
+#[derive(Default, Clone, Copy, Debug)]
+pub struct FrameCodec;
+
+impl Decoder for FrameCodec {
+    type Item = BytesMut;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let mut length = 0usize;
+        let mut header_length = 0;
+
+        loop {
+            let Some(&byte) = src.get(header_length) else {
+                return Ok(None); // The length itself got split
+            };
+
+            length |= ((byte & 0x7F) as usize) << (7 * header_length);
+            header_length += 1;
+
+            if byte & 0x80 == 0 {
+                break;
+            }
+
+            if header_length == MAX_LENGTH_BYTES {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "Packet length is longer than 3 bytes",
+                ));
+            }
+        }
+
+        if length == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "Packet length cannot be zero"));
+        }
+
+        if src.len() < header_length + length {
+            src.reserve(header_length + length - src.len());
+            return Ok(None);
+        }
+
+        src.advance(header_length);
+        Ok(Some(src.split_to(length)))
+    }
+}
+
+impl Encoder<Vec<u8>> for FrameCodec {
+    type Error = Error;
+
+    fn encode(&mut self, item: Vec<u8>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        if item.len() > MAX_PACKET_LENGTH {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Packet of {} bytes is larger than the maximum of {}",
+                    item.len(),
+                    MAX_PACKET_LENGTH
+                ),
+            ));
+        }
+
+        let mut length = Vec::with_capacity(MAX_LENGTH_BYTES);
+        data::write_varint(&mut length, item.len() as u32);
+
+        dst.reserve(length.len() + item.len());
+        dst.extend_from_slice(&length);
+        dst.extend_from_slice(&item);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(item: Vec<u8>) -> Vec<u8> {
+        let mut dst = BytesMut::new();
+        FrameCodec.encode(item, &mut dst).unwrap();
+        dst.to_vec()
+    }
+
+    #[test]
+    fn decodes_a_frame_delivered_one_byte_at_a_time() {
+        let item = vec![3; 300]; // Two byte length prefix
+        let frame = encode(item.clone());
+        assert_eq!(&frame[..2], &[0xAC, 0x02]);
+
+        let mut src = BytesMut::new();
+        for (index, byte) in frame.iter().enumerate() {
+            src.extend_from_slice(&[*byte]);
+            let decoded = FrameCodec.decode(&mut src).unwrap();
+
+            if index + 1 < frame.len() {
+                assert!(decoded.is_none());
+            } else {
+                assert_eq!(decoded.unwrap().to_vec(), item);
+            }
+        }
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn decodes_frames_coalesced_into_one_read() {
+        let mut src = BytesMut::new();
+        src.extend(encode(vec![1, 2]));
+        src.extend(encode(vec![3]));
+        src.extend(encode(vec![4, 5, 6]));
+        src.extend_from_slice(&[5, 7]); // The start of a fourth
+
+        assert_eq!(FrameCodec.decode(&mut src).unwrap().unwrap().to_vec(), vec![1, 2]);
+        assert_eq!(FrameCodec.decode(&mut src).unwrap().unwrap().to_vec(), vec![3]);
+        assert_eq!(FrameCodec.decode(&mut src).unwrap().unwrap().to_vec(), vec![4, 5, 6]);
+        assert!(FrameCodec.decode(&mut src).unwrap().is_none());
+        assert_eq!(src.to_vec(), vec![5, 7]);
+    }
+
+    #[test]
+    fn rejects_lengths_over_three_bytes() {
+        let mut src = BytesMut::from(&[0x80, 0x80, 0x80, 0x01][..]);
+        assert!(FrameCodec.decode(&mut src).is_err());
+
+        let mut src = BytesMut::from(&[0x00][..]);
+        assert!(FrameCodec.decode(&mut src).is_err());
+
+        assert!(FrameCodec.encode(vec![0; MAX_PACKET_LENGTH + 1], &mut BytesMut::new()).is_err());
+        assert_eq!(encode(vec![0; MAX_PACKET_LENGTH]).len(), MAX_PACKET_LENGTH + MAX_LENGTH_BYTES);
+    }
+
+    #[test]
+    fn waits_on_a_partial_length_prefix() {
+        let mut src = BytesMut::from(&[0xAC][..]);
+        assert!(FrameCodec.decode(&mut src).unwrap().is_none());
+        assert_eq!(src.to_vec(), vec![0xAC]);
+    }
+}
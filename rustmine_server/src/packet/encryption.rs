@@ -0,0 +1,132 @@
+use std::{
+    io::{Error, ErrorKind},
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use aes::{
+    Aes128,
+    cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit, generic_array::GenericArray},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+type Aes128Cfb8Encryptor = cfb8::Encryptor<Aes128>;
+type Aes128Cfb8Decryptor = cfb8::Decryptor<Aes128>;
+
+//
+// Wraps the connection's stream, passes bytes through untouched until encryption is enabled during login.
+// From then on everything is AES/CFB8 with the shared secret as both the key and the IV, in both directions.
+//
+
+pub struct CipherStream<S> {
+    inner: S,
+    encryptor: Option<Aes128Cfb8Encryptor>,
+    decryptor: Option<Aes128Cfb8Decryptor>,
+    pending: Vec<u8>, // Encrypted bytes the inner stream hasn't taken yet
+}
+
+impl<S> CipherStream<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            encryptor: None,
+            decryptor: None,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn enable_encryption(&mut self, shared_secret: &[u8]) -> Result<(), Error> {
+        let invalid = |_| Error::new(ErrorKind::InvalidData, "Shared secret must be 16 bytes");
+
+        self.encryptor = Some(Aes128Cfb8Encryptor::new_from_slices(shared_secret, shared_secret).map_err(invalid)?);
+        self.decryptor = Some(Aes128Cfb8Decryptor::new_from_slices(shared_secret, shared_secret).map_err(invalid)?);
+        Ok(())
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encryptor.is_some()
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S: AsyncWrite + Unpin> CipherStream<S> {
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        while !self.pending.is_empty() {
+            let written = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.pending))?;
+            if written == 0 {
+                return Poll::Ready(Err(ErrorKind::WriteZero.into()));
+            }
+
+            self.pending.drain(..written);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for CipherStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        let this = self.get_mut();
+        let already_filled = buf.filled().len();
+
+        ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+
+        if let Some(decryptor) = &mut this.decryptor {
+            // CFB8 works on one byte blocks, so the stream can be decrypted as it comes in
+            for byte in buf.filled_mut()[already_filled..].chunks_mut(1) {
+                decryptor.decrypt_block_mut(GenericArray::from_mut_slice(byte));
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for CipherStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        let this = self.get_mut();
+        if this.encryptor.is_none() {
+            return Pin::new(&mut this.inner).poll_write(cx, buf);
+        }
+
+        // The cipher state moves forward as bytes are encrypted, so they can't be taken back once encrypted.
+        // Anything the inner stream doesn't accept right away stays queued until the next write or flush.
+        ready!(this.poll_drain(cx))?;
+
+        this.pending.extend_from_slice(buf);
+        if let Some(encryptor) = &mut this.encryptor {
+            for byte in this.pending.chunks_mut(1) {
+                encryptor.encrypt_block_mut(GenericArray::from_mut_slice(byte));
+            }
+        }
+
+        if let Poll::Ready(Err(e)) = this.poll_drain(cx) {
+            return Poll::Ready(Err(e));
+        }
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
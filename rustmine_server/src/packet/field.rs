@@ -0,0 +1,272 @@
+use std::io::{Error, ErrorKind};
+
+use rustmine_lib::{
+    common::{configuration_state::ConfigKnownPackEntry, position::BlockPosition},
+    data,
+    game_profile::GameProfileProperty,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use uuid::Uuid;
+
+// Building blocks used by #[derive(Packet)] to encode and decode fields.
+
+pub trait PacketField: Sized {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error>;
+    fn write(&self, buffer: &mut Vec<u8>);
+}
+
+/// Types that are sent as a VarInt, used by fields marked with `#[packet(varint)]`.
+pub trait VarIntField: Sized {
+    fn read_varint(buffer: &[u8], position: &mut usize) -> Result<Self, Error>;
+    fn write_varint(&self, buffer: &mut Vec<u8>);
+}
+
+impl VarIntField for u32 {
+    fn read_varint(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_varint(buffer, position)
+    }
+
+    fn write_varint(&self, buffer: &mut Vec<u8>) {
+        data::write_varint(buffer, *self);
+    }
+}
+
+impl VarIntField for i32 {
+    fn read_varint(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_varint(buffer, position).map(|value| value as i32)
+    }
+
+    fn write_varint(&self, buffer: &mut Vec<u8>) {
+        data::write_varint(buffer, *self as u32);
+    }
+}
+
+impl PacketField for bool {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_bool(buffer, position)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_bool(buffer, *self);
+    }
+}
+
+impl PacketField for u8 {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_byte(buffer, position)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_byte(buffer, *self);
+    }
+}
+
+impl PacketField for i8 {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_byte(buffer, position).map(|value| value as i8)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_byte(buffer, *self as u8);
+    }
+}
+
+impl PacketField for u16 {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_ushort(buffer, position)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_ushort(buffer, *self);
+    }
+}
+
+impl PacketField for i32 {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_int(buffer, position)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_int(buffer, *self);
+    }
+}
+
+impl PacketField for u64 {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_long(buffer, position)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_long(buffer, *self);
+    }
+}
+
+impl PacketField for i64 {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_long(buffer, position).map(|value| value as i64)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_long(buffer, *self as u64);
+    }
+}
+
+impl PacketField for f32 {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_float(buffer, position)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_float(buffer, *self);
+    }
+}
+
+impl PacketField for f64 {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_double(buffer, position)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_double(buffer, *self);
+    }
+}
+
+impl PacketField for String {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_string(buffer, position)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_string(buffer, self);
+    }
+}
+
+impl PacketField for Uuid {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_uuid(buffer, position)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_uuid(buffer, self);
+    }
+}
+
+impl PacketField for BlockPosition {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        data::read_position(buffer, position)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_position(buffer, self);
+    }
+}
+
+// Fixed length byte arrays, such as signatures and fixed bit sets
+impl<const N: usize> PacketField for [u8; N] {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        let bytes = data::read_bytes(buffer, position, N)?;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_bytes(buffer, self);
+    }
+}
+
+impl PacketField for ConfigKnownPackEntry {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        let namespace = data::read_string(buffer, position)?;
+        let name = data::read_string(buffer, position)?;
+        let version = data::read_string(buffer, position)?;
+
+        Ok(ConfigKnownPackEntry {
+            name: format!("{}:{}", namespace, name),
+            version,
+        })
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        let (namespace, name) = self.name.split_once(":").unwrap();
+
+        data::write_string(buffer, namespace);
+        data::write_string(buffer, name);
+        data::write_string(buffer, &self.version);
+    }
+}
+
+impl PacketField for GameProfileProperty {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        let name = data::read_string(buffer, position)?;
+        let value = data::read_string(buffer, position)?;
+        let signature = match data::read_bool(buffer, position)? {
+            true => Some(data::read_string(buffer, position)?),
+            false => None,
+        };
+
+        Ok(GameProfileProperty { name, value, signature })
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_string(buffer, &self.name);
+        data::write_string(buffer, &self.value);
+        data::write_bool(buffer, self.signature.is_some());
+        if let Some(signature) = &self.signature {
+            data::write_string(buffer, signature);
+        }
+    }
+}
+
+pub fn read_remaining(buffer: &[u8], position: &mut usize) -> Vec<u8> {
+    let remaining = buffer[*position..].to_vec();
+    *position = buffer.len();
+    remaining
+}
+
+pub fn read_json<T: DeserializeOwned>(buffer: &[u8], position: &mut usize) -> Result<T, Error> {
+    let json = data::read_string(buffer, position)?;
+    serde_json::from_str(&json).map_err(|e| Error::new(ErrorKind::InvalidData, format!("JSON read error: {e}")))
+}
+
+pub fn write_json<T: Serialize>(value: &T, buffer: &mut Vec<u8>) {
+    data::write_string(buffer, &serde_json::to_string(value).unwrap());
+}
+
+// Since 1.20.2 the root tag sent over the network has no name, fastnbt always writes one (empty) so it gets cut out.
+
+pub fn read_nbt<T: DeserializeOwned>(buffer: &[u8], position: &mut usize) -> Result<T, Error> {
+    data::read_network_nbt(buffer, position)
+}
+
+pub fn write_nbt<T: Serialize>(value: &T, buffer: &mut Vec<u8>) {
+    let mut named = Vec::new();
+    data::write_nbt(&mut named, value).unwrap();
+
+    buffer.push(named[0]);
+    buffer.extend_from_slice(&named[3..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Tag {
+        name: String,
+        values: Vec<i32>,
+    }
+
+    #[test]
+    fn reads_only_the_nbt_it_takes() {
+        let tag = Tag { name: "test".to_string(), values: vec![1, 2, 3] };
+
+        let mut buffer = Vec::new();
+        write_nbt(&tag, &mut buffer);
+        data::write_varint(&mut buffer, 300);
+
+        let mut position = 0;
+        assert_eq!(read_nbt::<Tag>(&buffer, &mut position).unwrap(), tag);
+        assert_eq!(data::read_varint(&buffer, &mut position).unwrap(), 300);
+        assert_eq!(position, buffer.len());
+    }
+}
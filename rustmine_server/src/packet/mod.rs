@@ -4,118 +4,127 @@ use std::{
     sync::Arc,
 };
 
+use bytes::BytesMut;
 use flate2::{Compression, read::ZlibDecoder, write::ZlibEncoder};
+use futures::{Stream, StreamExt};
 use rustmine_lib::data;
-use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    net::TcpStream,
-};
+use tokio_util::codec::Decoder;
 
 pub mod clientbound;
+pub mod codec;
+pub mod encryption;
+pub mod field;
+pub mod registry;
 pub mod serverbound;
+pub mod writer;
 
-pub type RawPacket = (u32, u32, Vec<u8>);
-pub async fn read_packet(
-    stream: &mut TcpStream,
-    compression_threshold: u32,
-) -> Result<RawPacket, Box<dyn std::error::Error>> {
-    let mut temp_buffer = vec![0u8; 5];
-    stream.read_exact(&mut temp_buffer[..1]).await?;
-
-    let mut position = 0;
-    let mut slice = &temp_buffer[..];
-    let packet_length = data::read_varint(&mut slice, &mut position)?;
+pub use rustmine_macros::Packet;
 
-    if packet_length == 0 {
-        return Err(Box::new(Error::new(
-            ErrorKind::InvalidData,
-            "Packet length cannot be zero",
-        )));
-    }
+use crate::{packet::codec::FrameCodec, player::State};
 
-    let mut buffer = vec![0u8; packet_length as usize];
-    stream.read_exact(&mut buffer).await?;
+pub type RawPacket = (u32, u32, Vec<u8>);
 
-    let mut position = 0;
-    let mut slice = &buffer[..];
+// Same cap as vanilla, anything claiming to inflate to more than this is rejected before decompressing
+pub const MAX_DECOMPRESSED_LENGTH: u32 = 8 * 1024 * 1024;
 
-    if compression_threshold == 0 {
-        let packet_id = data::read_varint(&mut slice, &mut position)?;
-        let data = slice[position..].to_vec();
-        return Ok((packet_length, packet_id, data));
-    }
+#[derive(Clone, Copy, Debug)]
+pub struct CompressionSettings {
+    pub threshold: u32, // Packets of at least this many bytes get compressed
+    pub level: u32,     // zlib level, from 0 to 9
+}
 
-    // Read Data Length
-    let data_length = data::read_varint(&mut slice, &mut position)?;
-
-    let (packet_id, data) = if data_length >= compression_threshold {
-        let compressed_data = &slice[position..];
-        let mut decoder = ZlibDecoder::new(compressed_data);
-        let mut decompressed_data = Vec::new();
-        decoder.read_to_end(&mut decompressed_data)?;
-
-        let mut position = 0;
-        let mut slice = &decompressed_data[..];
-        let packet_id = data::read_varint(&mut slice, &mut position)?;
-        let data = slice[position..].to_vec();
-
-        (packet_id, data)
-    } else {
-        // Uncompressed packet
-        let packet_id = data::read_varint(&mut slice, &mut position)?;
-        let data = slice[position..].to_vec();
-        (packet_id, data)
-    };
+pub async fn read_packet<S: Stream<Item = Result<BytesMut, Error>> + Unpin>(
+    frames: &mut S,
+    compression: Option<CompressionSettings>,
+) -> Result<RawPacket, Box<dyn std::error::Error>> {
+    let frame = frames
+        .next()
+        .await
+        .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "Connection closed"))??;
 
-    Ok((packet_length, packet_id, data))
+    let (packet_id, data) = read_packet_body(&frame, compression)?;
+    Ok((frame.len() as u32, packet_id, data))
 }
 
 pub fn read_packet_from_bytes(
-    mut slice: &[u8],
-    compression_threshold: u32,
+    slice: &[u8],
+    compression: Option<CompressionSettings>,
 ) -> Result<RawPacket, Error> {
+    let mut buffer = BytesMut::from(slice);
+    let frame = FrameCodec
+        .decode(&mut buffer)?
+        .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "Packet too short"))?;
+
+    let (packet_id, data) = read_packet_body(&frame, compression)?;
+    Ok((frame.len() as u32, packet_id, data))
+}
+
+// Everything after the Packet Length, decompressing it first if needed
+fn read_packet_body(
+    body: &[u8],
+    compression: Option<CompressionSettings>,
+) -> Result<(u32, Vec<u8>), Error> {
     let mut position = 0;
 
-    let packet_length = data::read_varint(&mut slice, &mut position)?;
-    if slice.len() < packet_length as usize {
-        return Err(Error::new(ErrorKind::UnexpectedEof, "Packet too short"));
+    let Some(compression) = compression else {
+        let packet_id = data::read_varint(body, &mut position)?;
+        return Ok((packet_id, body[position..].to_vec()));
+    };
+
+    // Read Data Length, zero means the packet was sent uncompressed
+    let data_length = data::read_varint(body, &mut position)?;
+    if data_length == 0 {
+        let packet_id = data::read_varint(body, &mut position)?;
+        return Ok((packet_id, body[position..].to_vec()));
+    }
+
+    if data_length < compression.threshold {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Badly compressed packet: size of {} is below the threshold of {}",
+                data_length, compression.threshold
+            ),
+        ));
     }
 
-    if compression_threshold == 0 {
-        let packet_id = data::read_varint(&mut slice, &mut position)?;
-        let data = slice[position..].to_vec();
-        return Ok((packet_length, packet_id, data));
+    if data_length > MAX_DECOMPRESSED_LENGTH {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Badly compressed packet: size of {} is larger than the maximum of {}",
+                data_length, MAX_DECOMPRESSED_LENGTH
+            ),
+        ));
     }
 
-    let data_length = data::read_varint(&mut slice, &mut position)?;
-
-    let (packet_id, data) = if data_length >= compression_threshold {
-        let compressed_data = &slice[position..];
-        let mut decoder = ZlibDecoder::new(compressed_data);
-        let mut decompressed_data = Vec::new();
-        decoder.read_to_end(&mut decompressed_data)?;
-
-        let mut position = 0;
-        let mut slice = &decompressed_data[..];
-        let packet_id = data::read_varint(&mut slice, &mut position)?;
-        let data = slice[position..].to_vec();
-
-        (packet_id, data)
-    } else {
-        // Uncompressed packet
-        let packet_id = data::read_varint(&mut slice, &mut position)?;
-        let data = slice[position..].to_vec();
-        (packet_id, data)
-    };
+    // Never inflate past the declared length, one extra byte is enough to tell it lied
+    let mut decompressed_data = Vec::with_capacity(data_length as usize);
+    ZlibDecoder::new(&body[position..])
+        .take(data_length as u64 + 1)
+        .read_to_end(&mut decompressed_data)?;
 
-    Ok((packet_length, packet_id, data))
+    if decompressed_data.len() != data_length as usize {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Badly compressed packet: declared a size of {} but got {}",
+                data_length,
+                decompressed_data.len()
+            ),
+        ));
+    }
+
+    let mut position = 0;
+    let packet_id = data::read_varint(&decompressed_data, &mut position)?;
+    Ok((packet_id, decompressed_data[position..].to_vec()))
 }
 
-pub(crate) async fn write_packet(
-    packet: &dyn Packet,
-    cnx: &mut TcpStream,
-    compression_threshold: u32,
-) -> Result<(), Error> {
+// Everything that goes after the Packet Length, the frame codec adds that once the packet is written
+pub fn encode_packet(
+    packet: &dyn ClientboundPacket,
+    compression: Option<CompressionSettings>,
+) -> Result<Vec<u8>, Error> {
     let mut buffer = Vec::new();
 
     // Write packet ID first
@@ -132,46 +141,47 @@ pub(crate) async fn write_packet(
 
     let mut final_buffer = Vec::new();
 
-    if compression_threshold == 0 {
-        // No compression - use original format
-        data::write_varint(&mut final_buffer, uncompressed_length);
-        final_buffer.extend(buffer);
-    } else if uncompressed_length >= compression_threshold {
-        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
-        encoder.write_all(&buffer)?;
-        let compressed_data = encoder.finish()?;
-
-        let total_compressed_length =
-            compressed_data.len() as u32 + data::varint_size(uncompressed_length) as u32;
-
-        data::write_varint(&mut final_buffer, total_compressed_length);
-        data::write_varint(&mut final_buffer, uncompressed_length);
+    match compression {
+        None => {
+            // No compression - use original format
+            final_buffer.extend(buffer);
+        }
+        Some(compression) if uncompressed_length >= compression.threshold => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::new(compression.level.min(9)));
+            encoder.write_all(&buffer)?;
+            let compressed_data = encoder.finish()?;
 
-        final_buffer.extend(compressed_data);
-    } else {
-        let uncompressed_length_with_indicator = uncompressed_length + data::varint_size(0) as u32;
+            data::write_varint(&mut final_buffer, uncompressed_length);
 
-        data::write_varint(&mut final_buffer, uncompressed_length_with_indicator);
-        data::write_varint(&mut final_buffer, 0);
-        final_buffer.extend(buffer);
+            final_buffer.extend(compressed_data);
+        }
+        Some(_) => {
+            data::write_varint(&mut final_buffer, 0);
+            final_buffer.extend(buffer);
+        }
     }
 
-    // Send packet
-    cnx.write_all(&final_buffer).await?;
-    cnx.flush().await?;
-
-    Ok(())
+    Ok(final_buffer)
 }
 
+// Implemented through #[derive(Packet)], see rustmine_macros for the attributes.
 pub trait Packet: Any + Send + Sync {
     fn id() -> u32
     where
         Self: Sized;
 
+    fn state() -> State
+    where
+        Self: Sized;
+
     fn packet_id(&self) -> u32; // This is a method to get the packet ID from an instance
+}
 
-    fn write_to(self: &Self, buffer: &mut Vec<u8>);
+pub trait ClientboundPacket: Packet {
+    fn write_to(&self, buffer: &mut Vec<u8>);
+}
 
+pub trait ServerboundPacket: Packet {
     fn read_from(
         id: u32,
         buffer: Vec<u8>,
@@ -202,100 +212,78 @@ pub(crate) fn upcast_packet<E, P: Packet>(
     buffer.map(|b| b as Box<dyn Packet + 'static>)
 }
 
-#[macro_export]
-macro_rules! dispatch_packet_event {
-    (
-        packet = $packet:expr,
-        server = $server:expr,
-        state = $state:expr,
-        connection = $connection:expr,
-        table = {
-            $(
-                $conn_state:pat => [ $( $ty:ty ),* $(,)? ]
-            ),* $(,)?
-        }
-    ) => {
-        match $state {
-            $(
-                $conn_state => {
-                    $(
-                        if $packet.packet_id() == <$ty>::id() {
-                            if let Ok(concrete) = Arc::downcast::<$ty>($packet) {
-                                let event = Arc::new(crate::event::player_events::PlayerSentPacket::<$ty> {
-                                    packet: concrete,
-                                    player_connection: $connection,
-                                });
-
-                                $server.lock().await.event_bus.dispatch(&event).await;
-
-                            }
-                        }
-                    )*
-                }
-            )*
-            _ => {
-                eprintln!("Unknown state or packet: {:?} in state {:?}", $packet.packet_id(), $state);
-            }
-        }
-    };
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::clientbound::play::PlayPluginMessagePacket;
 
-#[macro_export]
-macro_rules! packet_id {
-    // Generate the function to get the packet ID
-    ($id:literal) => {
-        fn id() -> u32
-        where
-            Self: Sized,
-        {
-            $id
-        }
+    const COMPRESSION: Option<CompressionSettings> = Some(CompressionSettings { threshold: 64, level: 6 });
 
-        fn packet_id(&self) -> u32 {
-            Self::id()
-        }
-    };
-}
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
 
-#[macro_export]
-macro_rules! id_match {
-    ($id:expr, $expected:expr) => {
-        if $id != $expected {
-            return Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("Packet ID mismatch: expected {}, got {}", $expected, $id),
-            )));
-        }
-    };
-}
+    // Data Length, then whatever follows it
+    fn compressed_body(data_length: u32, rest: &[u8]) -> Vec<u8> {
+        let mut body = Vec::new();
+        data::write_varint(&mut body, data_length);
+        body.extend_from_slice(rest);
+        body
+    }
 
-#[macro_export]
-macro_rules! clientbound_packet {
-    () => {
-        async fn read_from(
-            _: u32,
-            _: Vec<u8>,
-        ) -> Result<Box<Self>, Box<std::io::Error>> {
-            unimplemented!(
-                concat!(
-                    stringify!($crate::packet::Packet),
-                    " is a clientbound packet and should not be read from a buffer"
-                )
-            );
-        }
-    };
-}
+    fn plugin_message(size: usize) -> PlayPluginMessagePacket {
+        PlayPluginMessagePacket { channel: "test:data".to_string(), data: vec![7; size] }
+    }
+
+    #[test]
+    fn round_trips_above_and_below_the_threshold() {
+        for size in [10, 1000] {
+            let packet = plugin_message(size);
+            let body = encode_packet(&packet, COMPRESSION).unwrap();
+
+            // Below the threshold the Data Length is 0 and the rest goes as is
+            assert_eq!(body[0] == 0, size < 64);
 
-#[macro_export]
-macro_rules! serverbound_packet {
-    () => {
-        fn write_to(&self, _buffer: &mut Vec<u8>) {
-            unimplemented!(
-                concat!(
-                    stringify!($crate::packet::Packet),
-                    " is a serverbound packet and should not be written to a buffer"
-                )
-            );
+            let (packet_id, data) = read_packet_body(&body, COMPRESSION).unwrap();
+            let mut expected = Vec::new();
+            packet.write_to(&mut expected);
+            assert_eq!((packet_id, data), (PlayPluginMessagePacket::id(), expected));
         }
-    };
+    }
+
+    #[test]
+    fn rejects_compressed_packets_below_the_threshold() {
+        let inner = [0x18, 1, 2, 3];
+        let body = compressed_body(inner.len() as u32, &zlib(&inner));
+        assert!(read_packet_body(&body, COMPRESSION).is_err());
+    }
+
+    #[test]
+    fn rejects_declared_lengths_over_the_maximum() {
+        let body = compressed_body(MAX_DECOMPRESSED_LENGTH + 1, &zlib(&[0x18]));
+        let error = read_packet_body(&body, COMPRESSION).unwrap_err();
+        assert!(error.to_string().contains("larger than the maximum"));
+    }
+
+    // A few KiB of zeros that inflate to 10 MiB, declared as 100 bytes
+    #[test]
+    fn stops_inflating_past_the_declared_length() {
+        let bomb = zlib(&vec![0; 10 * 1024 * 1024]);
+        let body = compressed_body(100, &bomb);
+        let error = read_packet_body(&body, COMPRESSION).unwrap_err();
+        assert!(error.to_string().contains("declared a size of 100 but got 101"));
+    }
+
+    #[test]
+    fn reads_whole_frames_from_bytes() {
+        let body = encode_packet(&plugin_message(1000), COMPRESSION).unwrap();
+        let mut frame = Vec::new();
+        data::write_varint(&mut frame, body.len() as u32);
+        frame.extend(&body);
+
+        let (length, packet_id, _) = read_packet_from_bytes(&frame, COMPRESSION).unwrap();
+        assert_eq!((length, packet_id), (body.len() as u32, PlayPluginMessagePacket::id()));
+    }
 }
@@ -0,0 +1,181 @@
+use std::{collections::HashMap, io::ErrorKind, pin::Pin, sync::Arc};
+
+use tokio::sync::{Mutex, RwLock};
+
+use crate::{
+    event::{player_events::PlayerSentPacket, EventBus},
+    packet::{
+        self,
+        clientbound::FinishConfigurationPacket,
+        serverbound::{configuration, handshake::HandshakePacket, login, play, status},
+        Packet, ServerboundPacket,
+    },
+    player::{PlayerConnection, State},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PacketDirection {
+    Serverbound,
+    Clientbound,
+}
+
+type PacketDecoder = Box<
+    dyn Fn(
+            u32,
+            Vec<u8>,
+        ) -> Pin<Box<dyn Future<Output = Result<Box<dyn Packet>, Box<std::io::Error>>> + Send>>
+        + Send
+        + Sync,
+>;
+
+type PacketEventDispatcher = Box<
+    dyn Fn(Arc<dyn Packet>, PlayerConnection, Arc<EventBus>) -> Pin<Box<dyn Future<Output = ()> + Send>>
+        + Send
+        + Sync,
+>;
+
+struct PacketRegistration {
+    decoder: PacketDecoder,
+    dispatcher: PacketEventDispatcher,
+}
+
+//
+// Every packet the server knows about lives here, keyed by the state it is valid in, its direction and its id.
+// Registering a packet under an existing key replaces it, which is how plugins override vanilla packets.
+//
+
+pub struct PacketRegistry {
+    packets: RwLock<HashMap<(State, PacketDirection, u32), Arc<PacketRegistration>>>,
+}
+
+impl Default for PacketRegistry {
+    fn default() -> Self {
+        let mut packets = HashMap::new();
+
+        macro_rules! register_all {
+            ($( $ty:ty ),* $(,)?) => {
+                $( insert_registration::<$ty>(&mut packets); )*
+            };
+        }
+
+        register_all! {
+            HandshakePacket,
+            status::StatusRequestPacket,
+            status::StatusPingPacket,
+            login::LoginStartPacket,
+            login::EncryptionResponsePacket,
+            login::LoginPluginResponsePacket,
+            login::LoginAcknowledgedPacket,
+            login::LoginCookieResponsePacket,
+            configuration::ClientInformationConfigPacket,
+            configuration::ConfigurationPluginMessagePacket,
+            FinishConfigurationPacket,
+            configuration::ClientKnownPacksPacket,
+            configuration::ConfigurationKeepAlivePacket,
+            configuration::ConfigurationCookieResponsePacket,
+            configuration::ConfigurationResourcePackResponsePacket,
+            play::ConfirmTeleportationPacket,
+            play::ChatMessagePacket,
+            play::ClientTickEndPacket,
+            play::CloseContainerPacket,
+            play::PlayKeepAlivePacket,
+            play::SetPlayerPositionPacket,
+            play::SetPlayerPositionAndRotationPacket,
+            play::SetPlayerRotationPacket,
+            play::SetPlayerMovementFlagsPacket,
+            play::PlayerActionPacket,
+            play::UseItemOnPacket,
+            play::PlayCookieResponsePacket,
+            play::PlayResourcePackResponsePacket,
+            play::ConfigurationAcknowledgedPacket,
+            play::PlayPluginMessagePacket,
+        }
+
+        Self { packets: RwLock::new(packets) }
+    }
+}
+
+fn insert_registration<P: ServerboundPacket>(
+    packets: &mut HashMap<(State, PacketDirection, u32), Arc<PacketRegistration>>,
+) {
+    let registration = PacketRegistration {
+        decoder: Box::new(|id, buffer| {
+            Box::pin(async move { packet::upcast_packet(P::read_from(id, buffer).await) })
+        }),
+        dispatcher: Box::new(|packet, connection, event_bus| {
+            Box::pin(async move {
+                if let Ok(concrete) = packet::downcast_packet::<P>(packet) {
+                    let event = Arc::new(PlayerSentPacket::<P> {
+                        packet: concrete,
+                        player_connection: Mutex::new(connection),
+                    });
+
+                    event_bus.dispatch(&event).await;
+                }
+            })
+        }),
+    };
+
+    packets.insert(
+        (P::state(), PacketDirection::Serverbound, P::id()),
+        Arc::new(registration),
+    );
+}
+
+impl PacketRegistry {
+    /// Registers `P` under its own state and id, replacing whatever was registered there before.
+    pub async fn register<P: ServerboundPacket>(&self) {
+        let mut packets = self.packets.write().await;
+        insert_registration::<P>(&mut packets);
+    }
+
+    pub async fn is_registered(&self, state: &State, direction: PacketDirection, id: u32) -> bool {
+        self.packets
+            .read()
+            .await
+            .contains_key(&(state.clone(), direction, id))
+    }
+
+    async fn registration(
+        &self,
+        state: &State,
+        direction: PacketDirection,
+        id: u32,
+    ) -> Option<Arc<PacketRegistration>> {
+        self.packets
+            .read()
+            .await
+            .get(&(state.clone(), direction, id))
+            .cloned() // Don't hold the lock while decoding or dispatching
+    }
+
+    pub(crate) async fn decode(
+        &self,
+        state: &State,
+        direction: PacketDirection,
+        id: u32,
+        buffer: Vec<u8>,
+    ) -> Result<Box<dyn Packet>, Box<std::io::Error>> {
+        match self.registration(state, direction, id).await {
+            Some(registration) => (registration.decoder)(id, buffer).await,
+            None => Err(Box::new(std::io::Error::new(
+                ErrorKind::Unsupported,
+                format!("Unknown packet id for {:?}: {}", state, id),
+            ))),
+        }
+    }
+
+    /// Fires the [`PlayerSentPacket`] event matching the concrete type of `packet`.
+    pub(crate) async fn dispatch(
+        &self,
+        state: &State,
+        direction: PacketDirection,
+        packet: Arc<dyn Packet>,
+        connection: PlayerConnection,
+        event_bus: Arc<EventBus>,
+    ) {
+        if let Some(registration) = self.registration(state, direction, packet.packet_id()).await {
+            (registration.dispatcher)(packet, connection, event_bus).await;
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x01, state = configuration, direction = serverbound)]
+pub struct ConfigurationCookieResponsePacket {
+    pub key: String,
+    #[packet(optional, length_prefixed)]
+    pub payload: Option<Vec<u8>>, // None if the client has no cookie under that key
+}
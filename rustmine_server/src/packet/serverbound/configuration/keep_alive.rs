@@ -0,0 +1,7 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x04, state = configuration, direction = serverbound)]
+pub struct ConfigurationKeepAlivePacket {
+    pub keep_alive_id: u64,
+}
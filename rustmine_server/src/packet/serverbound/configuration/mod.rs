@@ -1,9 +1,5 @@
-use std::vec;
-
 use crate::{
-    packet::{
-        self, clientbound::{self, configuration::ConfigSelectKnownPacksPacket, FinishConfigurationPacket}, Packet
-    },
+    packet::clientbound::FinishConfigurationPacket,
     player::PlayerConnection,
 };
 
@@ -16,89 +12,39 @@ pub use config_plugin_message::*;
 
 mod client_known_packs;
 pub use client_known_packs::*;
-use rustmine_lib::common::configuration_state::ConfigKnownPackEntry;
-
-pub(crate) async fn handle_configuration(
-    cnx: &mut PlayerConnection,
-) -> Result<(), Box<std::io::Error>> {
-    let packet = cnx.read_packet().await?;
 
-    let config_plugin_message = packet::downcast_packet::<ConfigurationPluginMessagePacket>(packet)
-        .map_err(|_| {
-            Box::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Expected a ConfigurationPluginMessagePacket",
-            ))
-        })
-        .unwrap();
+mod keep_alive;
+pub use keep_alive::*;
 
-    let packet = cnx.read_packet().await?;
-    let client_info_packet = packet::downcast_packet::<ClientInformationConfigPacket>(packet)
-        .map_err(|_| {
-            Box::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Expected a ClientInformationConfigPacket",
-            ))
-        })
-        .unwrap();
+mod cookie_response;
+pub use cookie_response::*;
 
-    cnx.update_client_info(client_info_packet, config_plugin_message)
-        .await?;
+mod resource_pack_response;
+pub use resource_pack_response::*;
 
-    let brand_name = {
-        let server = cnx.server.lock().await;
-        server.brand_name.to_owned()
-    };
-
-    cnx.write_packet(
-        &clientbound::configuration::ConfigurationPluginMessagePacket::brand_packet(brand_name),
-    )
-    .await?;
-
-    // Send known packs to client
-    cnx.write_packet(&ConfigSelectKnownPacksPacket {
-        entries: vec![ConfigKnownPackEntry::minecraft_core()],
-    })
-    .await?;
+pub(crate) async fn handle_configuration(
+    cnx: &mut PlayerConnection,
+) -> Result<(), Box<std::io::Error>> {
+    configure(cnx, false).await
+}
 
-    let packet = cnx.read_packet().await?; // Downcast this later
-    let client_known_packs = packet::downcast_packet::<ClientKnownPacksPacket>(packet).unwrap();
+// Runs every configuration task in order, then moves the client to Play. The client only sends its brand and
+// information once, which read_packet picks up whenever they come, so this is also all there is to do when a Play
+// connection is sent back to configuration.
+pub(crate) async fn configure(
+    cnx: &mut PlayerConnection,
+    reconfiguring: bool,
+) -> Result<(), Box<std::io::Error>> {
+    let configuration_tasks = cnx.server.lock().await.configuration_tasks.clone();
 
-    if !client_known_packs
-        .known_packs
-        .contains(&ConfigKnownPackEntry::minecraft_core())
-    {
-        println!("Client doesn't know minecraft:core!")
+    for task in configuration_tasks.tasks().await {
+        task.run(cnx, reconfiguring).await?;
     }
 
+    // The client moves to Play as soon as it gets this, so no configuration keep alive may follow it
+    cnx.keep_alive.lock().await.set_paused(true);
     cnx.write_packet(&FinishConfigurationPacket).await?;
-    let packet = cnx.read_packet().await?;
-
-    if packet.packet_id() != FinishConfigurationPacket::id() {
-        return Err(
-            Box::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Expected client to acknowledge configuration end.",
-            ))
-        );
-    }
+    cnx.wait_for_packet::<FinishConfigurationPacket>().await?;
 
     Ok(())
 }
-
-pub(crate) async fn read_packet(
-    id: u32,
-    buffer: Vec<u8>,
-) -> Result<Box<dyn Packet + 'static>, Box<std::io::Error>> {
-    match id {
-        0x00 => packet::upcast_packet(ClientInformationConfigPacket::read_from(id, buffer).await),
-        0x02 => packet::upcast_packet(ConfigurationPluginMessagePacket::read_from(id, buffer).await),
-        0x03 => packet::upcast_packet(FinishConfigurationPacket::read_from(id, buffer).await),
-        0x07 => packet::upcast_packet(ClientKnownPacksPacket::read_from(id, buffer).await), // Screams macro
-
-        _ => Err(Box::new(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("Unknown packet id for Configuration: {}", id),
-        ))),
-    }
-}
@@ -0,0 +1,11 @@
+use uuid::Uuid;
+
+use crate::{packet::Packet, resource_pack::ResourcePackResult};
+
+#[derive(Packet)]
+#[packet(id = 0x06, state = configuration, direction = serverbound)]
+pub struct ConfigurationResourcePackResponsePacket {
+    pub id: Uuid,
+    #[packet(varint)]
+    pub result: ResourcePackResult,
+}
@@ -1,63 +1,71 @@
-use std::io::Error;
+use std::io::ErrorKind;
 
+use rand::RngCore;
 use rustmine_lib::game_profile::GameProfile;
 use uuid::Uuid;
 
 use crate::{
-    id_match,
-    packet::{self, Packet, clientbound::login::LoginSuccessPacket, data},
-    packet_id, serverbound_packet,
+    auth,
+    config::ForwardingMode,
+    forwarding::{self, ForwardedPlayer},
+    packet::{
+        self, Packet,
+        clientbound::login::{
+            EncryptionRequestPacket, LoginPluginRequestPacket, LoginSuccessPacket, SetCompressionPacket,
+        },
+        serverbound::handshake::HandshakePacket,
+        CompressionSettings,
+    },
+    player::PlayerConnection,
 };
 
+#[derive(Packet)]
+#[packet(id = 0x00, state = login, direction = serverbound)]
 pub struct LoginStartPacket {
     pub username: String,
     pub uuid: Uuid,
 }
 
-impl Packet for LoginStartPacket {
-    packet_id!(0x00);
-    serverbound_packet!();
+#[derive(Packet)]
+#[packet(id = 0x01, state = login, direction = serverbound)]
+pub struct EncryptionResponsePacket {
+    #[packet(length_prefixed)]
+    pub shared_secret: Vec<u8>, // Both encrypted with the server's public key
+    #[packet(length_prefixed)]
+    pub verify_token: Vec<u8>,
+}
 
-    async fn read_from(id: u32, buffer: Vec<u8>) -> Result<Box<Self>, Box<std::io::Error>> {
-        id_match!(id, Self::id());
+// Data is None when the client didn't understand the request
+#[derive(Packet)]
+#[packet(id = 0x02, state = login, direction = serverbound)]
+pub struct LoginPluginResponsePacket {
+    #[packet(varint)]
+    pub message_id: u32,
+    #[packet(optional, with = remaining)]
+    pub data: Option<Vec<u8>>,
+}
 
-        let mut position = 0 as usize;
-        Ok(Box::new(LoginStartPacket {
-            username: data::read_string(&buffer, &mut position).unwrap(),
-            uuid: data::read_uuid(&buffer, &mut position).unwrap(),
-        }))
+mod remaining {
+    pub fn read(buffer: &[u8], position: &mut usize) -> Result<Vec<u8>, std::io::Error> {
+        Ok(crate::packet::field::read_remaining(buffer, position))
     }
 }
 
+#[derive(Packet)]
+#[packet(id = 0x03, state = login, direction = serverbound)]
 pub struct LoginAcknowledgedPacket {}
 
-impl Packet for LoginAcknowledgedPacket {
-    packet_id!(0x03);
-
-    async fn read_from(
-        id: u32,
-        _buffer: Vec<u8>,
-    ) -> Result<Box<LoginAcknowledgedPacket>, Box<std::io::Error>> {
-        id_match!(id, Self::id());
-        Ok(Box::new(LoginAcknowledgedPacket {}))
-    }
-
-    fn write_to(&self, _buffer: &mut Vec<u8>) {}
-}
-
-pub(crate) async fn read_packet(id: u32, buffer: Vec<u8>) -> Result<Box<dyn Packet>, Box<Error>> {
-    return match id {
-        0x00 => packet::upcast_packet(LoginStartPacket::read_from(id, buffer).await),
-        0x03 => packet::upcast_packet(LoginAcknowledgedPacket::read_from(id, buffer).await),
-        _ => Err(Box::new(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("Unknown packet id for Status: {}", id),
-        ))),
-    };
+#[derive(Packet)]
+#[packet(id = 0x04, state = login, direction = serverbound)]
+pub struct LoginCookieResponsePacket {
+    pub key: String,
+    #[packet(optional, length_prefixed)]
+    pub payload: Option<Vec<u8>>, // None if the client has no cookie under that key
 }
 
 pub(crate) async fn handle_login(
-    arg: &mut crate::player::PlayerConnection,
+    arg: &mut PlayerConnection,
+    handshake: &HandshakePacket,
 ) -> Result<(), Box<std::io::Error>> {
     let packet = arg.read_packet().await?;
 
@@ -67,20 +75,52 @@ pub(crate) async fn handle_login(
                 std::io::ErrorKind::InvalidData,
                 "Expected a LoginStartPacket",
             ))
-        })
-        .unwrap();
+        })?;
+
+    let (online_mode, forwarding_mode, compression_threshold, compression_level) = {
+        let server = arg.server.lock().await;
+        (
+            server.config.online_mode,
+            server.config.forwarding.clone(),
+            server.config.compression_threshold,
+            server.config.compression_level,
+        )
+    };
+
+    // Behind a proxy it's the proxy that authenticates, the connection itself is never encrypted
+    let profile = match forwarding_mode {
+        ForwardingMode::Legacy => {
+            let forwarded = forwarding::parse_legacy(&handshake.server_address, &login_start_packet.username)?;
+            arg.set_address(forwarded.address);
+            forwarded.profile
+        }
+        ForwardingMode::Modern { secret } => {
+            let forwarded = modern_forwarding(arg, &secret).await?;
+            arg.set_address(forwarded.address);
+            forwarded.profile
+        }
+        ForwardingMode::None if online_mode => authenticate(arg, &login_start_packet.username).await?,
+        ForwardingMode::None => GameProfile {
+            username: login_start_packet.username.clone(),
+            uuid: login_start_packet.uuid,
+            properties: vec![],
+        },
+    };
+
+    // Has to come after encryption is enabled and before Login Success
+    if let Some(threshold) = compression_threshold {
+        arg.write_packet(&SetCompressionPacket { threshold: threshold as i32 }).await?;
+        arg.enable_compression(CompressionSettings { threshold, level: compression_level }).await;
+    }
 
     arg.write_packet(&LoginSuccessPacket {
-        username: login_start_packet.username.clone(),
-        uuid: login_start_packet.uuid,
-        // TODO READ TEXTURES AND SIGNATURE
+        uuid: profile.uuid,
+        username: profile.username.clone(),
+        properties: profile.properties.clone(),
     })
     .await?;
 
-    arg.set_game_profile(GameProfile {
-        username: login_start_packet.username.clone(),
-        uuid: login_start_packet.uuid
-    }).await;
+    arg.set_game_profile(profile).await;
 
     let packet = arg.read_packet().await?;
     if packet.packet_id() != LoginAcknowledgedPacket::id() {
@@ -92,3 +132,100 @@ pub(crate) async fn handle_login(
 
     Ok(())
 }
+
+// Encryption Request/Response exchange, then asks the session verifier who this player actually is.
+async fn authenticate(
+    arg: &mut PlayerConnection,
+    username: &str,
+) -> Result<GameProfile, Box<std::io::Error>> {
+    let (key_pair, session_verifier) = {
+        let server = arg.server.lock().await;
+        (server.key_pair().map_err(std::io::Error::other)?, server.session_verifier.clone())
+    };
+
+    let mut verify_token = vec![0u8; 4];
+    rand::thread_rng().fill_bytes(&mut verify_token);
+
+    arg.write_packet(&EncryptionRequestPacket {
+        server_id: String::new(),
+        public_key: key_pair.public_key_der().to_vec(),
+        verify_token: verify_token.clone(),
+        should_authenticate: true,
+    })
+    .await?;
+
+    let packet = arg.read_packet().await?;
+    let response = packet::downcast_packet::<EncryptionResponsePacket>(packet).map_err(|_| {
+        Box::new(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "Expected an EncryptionResponsePacket",
+        ))
+    })?;
+
+    let invalid = |e: rsa::Error| std::io::Error::new(ErrorKind::InvalidData, format!("Failed to decrypt: {}", e));
+    let shared_secret = key_pair.decrypt(&response.shared_secret).map_err(invalid)?;
+    let returned_token = key_pair.decrypt(&response.verify_token).map_err(invalid)?;
+
+    if returned_token != verify_token {
+        return Err(Box::new(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "Verify token mismatch",
+        )));
+    }
+
+    arg.enable_encryption(&shared_secret).await?;
+
+    let server_hash = auth::server_hash("", &shared_secret, key_pair.public_key_der());
+    let ip = arg.address().map(|address| address.ip());
+
+    match session_verifier.has_joined(username, &server_hash, ip).await {
+        Ok(Some(profile)) => Ok(profile),
+        Ok(None) => Err(Box::new(std::io::Error::new(
+            ErrorKind::PermissionDenied,
+            "Failed to verify username",
+        ))),
+        Err(e) => Err(Box::new(std::io::Error::other(format!(
+            "Session verification failed: {}",
+            e
+        )))),
+    }
+}
+
+// Asks the proxy for the player's details through a login plugin request, a client without a proxy won't understand it
+async fn modern_forwarding(
+    arg: &mut PlayerConnection,
+    secret: &str,
+) -> Result<ForwardedPlayer, Box<std::io::Error>> {
+    let message_id = rand::thread_rng().next_u32() & 0x7FFF_FFFF;
+
+    arg.write_packet(&LoginPluginRequestPacket {
+        message_id,
+        channel: forwarding::VELOCITY_CHANNEL.to_string(),
+        data: vec![forwarding::MODERN_FORWARDING_VERSION],
+    })
+    .await?;
+
+    let packet = arg.read_packet().await?;
+    let response = packet::downcast_packet::<LoginPluginResponsePacket>(packet).map_err(|_| {
+        Box::new(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "Expected a LoginPluginResponsePacket",
+        ))
+    })?;
+
+    if response.message_id != message_id {
+        return Err(Box::new(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "Unexpected login plugin message id",
+        )));
+    }
+
+    let Some(data) = &response.data else {
+        return Err(Box::new(std::io::Error::new(
+            ErrorKind::PermissionDenied,
+            "This server requires you to connect with Velocity.",
+        )));
+    };
+
+    Ok(forwarding::parse_modern(secret.as_bytes(), data)?)
+}
@@ -1,4 +1,5 @@
 pub mod handshake;
 pub mod status;
 pub mod login;
-pub mod configuration;
\ No newline at end of file
+pub mod configuration;
+pub mod play;
\ No newline at end of file
@@ -0,0 +1,15 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x08, state = play, direction = serverbound)]
+pub struct ChatMessagePacket {
+    pub message: String,
+    pub timestamp: u64,
+    pub salt: u64,
+    #[packet(optional)]
+    pub signature: Option<[u8; 256]>,
+    #[packet(varint)]
+    pub message_count: u32,
+    pub acknowledged: [u8; 3], // Fixed BitSet of 20 bits
+    pub checksum: u8,
+}
@@ -0,0 +1,5 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x0C, state = play, direction = serverbound)]
+pub struct ClientTickEndPacket;
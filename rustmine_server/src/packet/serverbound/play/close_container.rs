@@ -0,0 +1,8 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x12, state = play, direction = serverbound)]
+pub struct CloseContainerPacket {
+    #[packet(varint)]
+    pub window_id: u32, // 0 is the player inventory
+}
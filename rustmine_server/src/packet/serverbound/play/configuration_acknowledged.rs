@@ -0,0 +1,5 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x0F, state = play, direction = serverbound)]
+pub struct ConfigurationAcknowledgedPacket;
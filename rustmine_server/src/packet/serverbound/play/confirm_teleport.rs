@@ -0,0 +1,8 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x00, state = play, direction = serverbound)]
+pub struct ConfirmTeleportationPacket {
+    #[packet(varint)]
+    pub teleport_id: u32,
+}
@@ -0,0 +1,9 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x14, state = play, direction = serverbound)]
+pub struct PlayCookieResponsePacket {
+    pub key: String,
+    #[packet(optional, length_prefixed)]
+    pub payload: Option<Vec<u8>>,
+}
@@ -0,0 +1,7 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x1B, state = play, direction = serverbound)]
+pub struct PlayKeepAlivePacket {
+    pub keep_alive_id: u64,
+}
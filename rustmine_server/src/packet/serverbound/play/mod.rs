@@ -0,0 +1,58 @@
+use std::io::ErrorKind;
+
+use crate::{packet, player::PlayerConnection};
+
+// Same as configuration, one file per packet (or group of closely related packets).
+mod confirm_teleport;
+pub use confirm_teleport::*;
+
+mod chat_message;
+pub use chat_message::*;
+
+mod client_tick_end;
+pub use client_tick_end::*;
+
+mod keep_alive;
+pub use keep_alive::*;
+
+mod player_movement;
+pub use player_movement::*;
+
+mod player_action;
+pub use player_action::*;
+
+mod use_item_on;
+pub use use_item_on::*;
+
+mod close_container;
+pub use close_container::*;
+
+mod cookie_response;
+pub use cookie_response::*;
+
+mod resource_pack_response;
+pub use resource_pack_response::*;
+
+mod configuration_acknowledged;
+pub use configuration_acknowledged::*;
+
+mod plugin_message;
+pub use plugin_message::*;
+
+pub(crate) async fn handle_play(cnx: &mut PlayerConnection) -> Result<(), Box<std::io::Error>> {
+    loop {
+        // Events for each packet are dispatched by read_packet, all that is left is to keep reading.
+        match cnx.read_packet().await {
+            // Sent back to configuration by PlayerConnection::reconfigure from another task
+            Ok(packet) if packet::downcast_packet::<ConfigurationAcknowledgedPacket>(packet.clone()).is_ok() => {
+                cnx.run_reconfiguration().await?;
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::Unsupported => {} // Packet we don't decode yet, already consumed
+            Err(e) => return Err(e),
+        }
+
+        // Channels registered or unregistered while the player is online reach it here, the client sends plenty
+        cnx.sync_plugin_channels().await?;
+    }
+}
@@ -0,0 +1,106 @@
+use std::io::{Error, ErrorKind};
+
+use rustmine_lib::{common::position::BlockPosition, data};
+
+use crate::packet::{
+    Packet,
+    field::{PacketField, VarIntField},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayerActionStatus {
+    StartedDigging,
+    CancelledDigging,
+    FinishedDigging,
+    DropItemStack,
+    DropItem,
+    ReleaseUseItem, // Shooting arrows, finishing eating, etc.
+    SwapItemInHand,
+}
+
+impl PlayerActionStatus {
+    pub fn from_id(id: u32) -> Result<PlayerActionStatus, Error> {
+        match id {
+            0 => Ok(PlayerActionStatus::StartedDigging),
+            1 => Ok(PlayerActionStatus::CancelledDigging),
+            2 => Ok(PlayerActionStatus::FinishedDigging),
+            3 => Ok(PlayerActionStatus::DropItemStack),
+            4 => Ok(PlayerActionStatus::DropItem),
+            5 => Ok(PlayerActionStatus::ReleaseUseItem),
+            6 => Ok(PlayerActionStatus::SwapItemInHand),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid player action status: {}", id),
+            )),
+        }
+    }
+}
+
+impl VarIntField for PlayerActionStatus {
+    fn read_varint(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        PlayerActionStatus::from_id(data::read_varint(buffer, position)?)
+    }
+
+    fn write_varint(&self, buffer: &mut Vec<u8>) {
+        data::write_varint(buffer, *self as u32);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockFace {
+    Bottom,
+    Top,
+    North,
+    South,
+    West,
+    East,
+}
+
+impl BlockFace {
+    pub fn from_id(id: u32) -> Result<BlockFace, Error> {
+        match id {
+            0 => Ok(BlockFace::Bottom),
+            1 => Ok(BlockFace::Top),
+            2 => Ok(BlockFace::North),
+            3 => Ok(BlockFace::South),
+            4 => Ok(BlockFace::West),
+            5 => Ok(BlockFace::East),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid block face: {}", id),
+            )),
+        }
+    }
+}
+
+// Player Action sends the face as a byte, Use Item On as a VarInt
+impl PacketField for BlockFace {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        BlockFace::from_id(data::read_byte(buffer, position)? as u32)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        data::write_byte(buffer, *self as u8);
+    }
+}
+
+impl VarIntField for BlockFace {
+    fn read_varint(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        BlockFace::from_id(data::read_varint(buffer, position)?)
+    }
+
+    fn write_varint(&self, buffer: &mut Vec<u8>) {
+        data::write_varint(buffer, *self as u32);
+    }
+}
+
+#[derive(Packet)]
+#[packet(id = 0x28, state = play, direction = serverbound)]
+pub struct PlayerActionPacket {
+    #[packet(varint)]
+    pub status: PlayerActionStatus,
+    pub location: BlockPosition,
+    pub face: BlockFace,
+    #[packet(varint)]
+    pub sequence: u32,
+}
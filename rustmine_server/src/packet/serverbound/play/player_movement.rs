@@ -0,0 +1,61 @@
+use std::io::Error;
+
+use crate::packet::{Packet, field::PacketField};
+
+// Shared by every movement packet, bit 0x01 is on ground and 0x02 is pushing against a wall
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MovementFlags(pub u8);
+
+impl MovementFlags {
+    pub fn on_ground(&self) -> bool {
+        self.0 & 0x01 != 0
+    }
+
+    pub fn pushing_against_wall(&self) -> bool {
+        self.0 & 0x02 != 0
+    }
+}
+
+impl PacketField for MovementFlags {
+    fn read(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        u8::read(buffer, position).map(MovementFlags)
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        self.0.write(buffer);
+    }
+}
+
+#[derive(Packet)]
+#[packet(id = 0x1D, state = play, direction = serverbound)]
+pub struct SetPlayerPositionPacket {
+    pub x: f64,
+    pub feet_y: f64,
+    pub z: f64,
+    pub flags: MovementFlags,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x1E, state = play, direction = serverbound)]
+pub struct SetPlayerPositionAndRotationPacket {
+    pub x: f64,
+    pub feet_y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub flags: MovementFlags,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x1F, state = play, direction = serverbound)]
+pub struct SetPlayerRotationPacket {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub flags: MovementFlags,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x20, state = play, direction = serverbound)]
+pub struct SetPlayerMovementFlagsPacket {
+    pub flags: MovementFlags,
+}
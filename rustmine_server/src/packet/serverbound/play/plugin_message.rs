@@ -0,0 +1,9 @@
+use crate::packet::Packet;
+
+#[derive(Packet)]
+#[packet(id = 0x15, state = play, direction = serverbound)]
+pub struct PlayPluginMessagePacket {
+    pub channel: String,
+    #[packet(remaining)]
+    pub data: Vec<u8>,
+}
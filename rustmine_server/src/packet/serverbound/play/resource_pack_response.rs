@@ -0,0 +1,11 @@
+use uuid::Uuid;
+
+use crate::{packet::Packet, resource_pack::ResourcePackResult};
+
+#[derive(Packet)]
+#[packet(id = 0x30, state = play, direction = serverbound)]
+pub struct PlayResourcePackResponsePacket {
+    pub id: Uuid,
+    #[packet(varint)]
+    pub result: ResourcePackResult,
+}
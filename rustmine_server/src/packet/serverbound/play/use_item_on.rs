@@ -0,0 +1,53 @@
+use std::io::{Error, ErrorKind};
+
+use rustmine_lib::{common::position::BlockPosition, data};
+
+use crate::packet::{Packet, field::VarIntField};
+
+use super::BlockFace;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hand {
+    MainHand,
+    OffHand,
+}
+
+impl Hand {
+    pub fn from_id(id: u32) -> Result<Hand, Error> {
+        match id {
+            0 => Ok(Hand::MainHand),
+            1 => Ok(Hand::OffHand),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid hand: {}", id),
+            )),
+        }
+    }
+}
+
+impl VarIntField for Hand {
+    fn read_varint(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        Hand::from_id(data::read_varint(buffer, position)?)
+    }
+
+    fn write_varint(&self, buffer: &mut Vec<u8>) {
+        data::write_varint(buffer, *self as u32);
+    }
+}
+
+#[derive(Packet)]
+#[packet(id = 0x3F, state = play, direction = serverbound)]
+pub struct UseItemOnPacket {
+    #[packet(varint)]
+    pub hand: Hand,
+    pub location: BlockPosition,
+    #[packet(varint)]
+    pub face: BlockFace,
+    pub cursor_x: f32,
+    pub cursor_y: f32,
+    pub cursor_z: f32,
+    pub inside_block: bool,
+    pub world_border_hit: bool,
+    #[packet(varint)]
+    pub sequence: u32,
+}
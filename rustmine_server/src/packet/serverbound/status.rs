@@ -1,62 +1,28 @@
-use std::io::Error;
+use std::sync::Arc;
 
 use crate::{
-    id_match,
-    packet::{self, clientbound::status::StatusPongPacket, data, Packet},
-    packet_id,
+    event::server_events::ServerListPing,
+    packet::{
+        self,
+        clientbound::status::{StatusPongPacket, StatusResponsePacket},
+        serverbound::handshake::HandshakePacket,
+        Packet,
+    },
 };
 
+#[derive(Packet)]
+#[packet(id = 0x00, state = status, direction = serverbound)]
 pub struct StatusRequestPacket {}
 
-impl Packet for StatusRequestPacket {
-    packet_id!(0x00);
-
-    async fn read_from(
-        id: u32,
-        _buffer: Vec<u8>,
-    ) -> Result<Box<StatusRequestPacket>, Box<std::io::Error>> {
-        id_match!(id, Self::id());
-        Ok(Box::new(StatusRequestPacket {}))
-    }
-
-    fn write_to(&self, _buffer: &mut Vec<u8>) {}
-}
-
+#[derive(Packet)]
+#[packet(id = 0x01, state = status, direction = serverbound)]
 pub struct StatusPingPacket {
     pub payload: u64,
 }
 
-impl Packet for StatusPingPacket {
-    packet_id!(0x01);
-
-    async fn read_from(
-        id: u32,
-        buffer: Vec<u8>,
-    ) -> Result<Box<StatusPingPacket>, Box<std::io::Error>> {
-        id_match!(id, Self::id());
-
-        let payload = data::read_long(&buffer, &mut 0).unwrap();
-        Ok(Box::new(StatusPingPacket { payload }))
-    }
-
-    fn write_to(&self, buffer: &mut Vec<u8>) {
-        data::write_long(buffer, self.payload);
-    }
-}
-
-pub(crate) async fn read_packet(id: u32, buffer: Vec<u8>) -> Result<Box<dyn Packet>, Box<Error>> {
-    return match id {
-        0x00 => packet::upcast_packet(StatusRequestPacket::read_from(id, buffer).await),
-        0x01 => packet::upcast_packet(StatusPingPacket::read_from(id, buffer).await),
-        _ => Err(Box::new(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("Unknown packet id for Status: {}", id),
-        ))),
-    };
-}
-
 pub(crate) async fn handle_status_request(
     arg: &mut crate::player::PlayerConnection,
+    handshake: &Arc<HandshakePacket>,
 ) -> Result<(), Box<std::io::Error>> {
     let packet = arg.read_packet().await?;
     if packet.packet_id() != StatusRequestPacket::id() {
@@ -66,6 +32,24 @@ pub(crate) async fn handle_status_request(
         )));
     }
 
+    let (response, event_bus) = {
+        let server = arg.server.lock().await;
+        (server.status_response(), server.event_bus.clone())
+    };
+
+    // Listeners get the built-in response and can hand back their own
+    let event = Arc::new(ServerListPing {
+        handshake: handshake.clone(),
+        address: arg.address(),
+        response,
+    });
+    let response = event_bus
+        .dispatch(&event)
+        .await
+        .unwrap_or_else(|| event.response.clone());
+
+    arg.write_packet(&StatusResponsePacket { response }).await?;
+
     let packet = arg.read_packet().await?;
     let status_ping = packet::downcast_packet::<StatusPingPacket>(packet).map_err(|_| {
         Box::new(std::io::Error::new(
@@ -84,5 +68,3 @@ pub(crate) async fn handle_status_request(
 
     Ok(())
 }
-
-
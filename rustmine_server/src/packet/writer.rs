@@ -0,0 +1,155 @@
+use std::io::{Error, ErrorKind};
+
+use futures::SinkExt;
+use tokio::{
+    net::tcp::OwnedWriteHalf,
+    sync::{mpsc, oneshot},
+    task,
+};
+use tokio_util::{codec::FramedWrite, sync::CancellationToken};
+
+use crate::packet::{codec::FrameCodec, encryption::CipherStream};
+
+// How many encoded packets can wait for the socket before writers have to wait for room
+pub const OUTBOUND_QUEUE_SIZE: usize = 512;
+
+enum Outbound {
+    Frame(Vec<u8>),
+    Flush(oneshot::Sender<Result<(), Error>>),
+    EnableEncryption(Vec<u8>, oneshot::Sender<Result<(), Error>>),
+}
+
+//
+// The sending half of a connection. Packets are encoded by whoever sends them and queued here,
+// a dedicated task writes them out in order so nobody has to wait on the socket itself.
+// The queue is flushed to the socket whenever it runs empty, so packets sent in a burst go out together.
+//
+
+#[derive(Clone)]
+pub struct PacketWriter {
+    sender: mpsc::Sender<Outbound>,
+}
+
+impl PacketWriter {
+    pub(crate) fn spawn(stream: CipherStream<OwnedWriteHalf>, closed: CancellationToken) -> Self {
+        let (sender, receiver) = mpsc::channel(OUTBOUND_QUEUE_SIZE);
+        task::spawn(write_loop(FramedWrite::new(stream, FrameCodec), receiver, closed));
+
+        Self { sender }
+    }
+
+    /// Queues a frame, waiting for room if the queue is full.
+    pub async fn send(&self, frame: Vec<u8>) -> Result<(), Error> {
+        self.sender
+            .send(Outbound::Frame(frame))
+            .await
+            .map_err(|_| closed())
+    }
+
+    /// Queues a frame without waiting, fails with [`ErrorKind::WouldBlock`] if the queue is full.
+    pub fn try_send(&self, frame: Vec<u8>) -> Result<(), Error> {
+        self.sender.try_send(Outbound::Frame(frame)).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => {
+                Error::new(ErrorKind::WouldBlock, "Outbound queue is full")
+            }
+            mpsc::error::TrySendError::Closed(_) => closed(),
+        })
+    }
+
+    /// Resolves once everything queued before it has been written to the socket.
+    pub async fn flush(&self) -> Result<(), Error> {
+        let (done, result) = oneshot::channel();
+        self.sender
+            .send(Outbound::Flush(done))
+            .await
+            .map_err(|_| closed())?;
+
+        result.await.map_err(|_| closed())?
+    }
+
+    // Packets queued before this are still written in plain text
+    pub(crate) async fn enable_encryption(&self, shared_secret: &[u8]) -> Result<(), Error> {
+        let (done, result) = oneshot::channel();
+        self.sender
+            .send(Outbound::EnableEncryption(shared_secret.to_vec(), done))
+            .await
+            .map_err(|_| closed())?;
+
+        result.await.map_err(|_| closed())?
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+}
+
+fn closed() -> Error {
+    Error::new(ErrorKind::BrokenPipe, "Connection closed")
+}
+
+async fn write_loop(
+    mut framed: FramedWrite<CipherStream<OwnedWriteHalf>, FrameCodec>,
+    mut receiver: mpsc::Receiver<Outbound>,
+    closed: CancellationToken,
+) {
+    // Ends once every handle is dropped, the connection is closed or the socket fails, dropping the write half closes it
+    loop {
+        let message = tokio::select! {
+            message = receiver.recv() => message,
+            _ = closed.cancelled() => {
+                // Nothing new gets in, but what was queued before closing still goes out
+                receiver.close();
+                while let Ok(message) = receiver.try_recv() {
+                    if let Outbound::Frame(frame) = message
+                        && framed.feed(frame).await.is_err()
+                    {
+                        return;
+                    }
+                }
+
+                let _ = framed.flush().await;
+                return;
+            }
+        };
+
+        let Some(message) = message else {
+            break;
+        };
+
+        let result = match message {
+            Outbound::Frame(frame) => framed.feed(frame).await,
+            Outbound::Flush(done) => {
+                let result = framed.flush().await;
+                let failed = result.is_err();
+                let _ = done.send(result);
+
+                if failed {
+                    break;
+                }
+                continue;
+            }
+            Outbound::EnableEncryption(shared_secret, done) => {
+                // Whatever is buffered was encoded before the switch
+                let result = match framed.flush().await {
+                    Ok(()) => framed.get_mut().enable_encryption(&shared_secret),
+                    Err(e) => Err(e),
+                };
+                let failed = result.is_err();
+                let _ = done.send(result);
+
+                if failed {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        if result.is_err() {
+            break;
+        }
+
+        if receiver.is_empty() && framed.flush().await.is_err() {
+            break;
+        }
+    }
+}
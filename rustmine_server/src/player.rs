@@ -1,20 +1,58 @@
-use std::{error::Error, io::ErrorKind, sync::Arc};
+use std::{
+    any::Any,
+    collections::{HashSet, VecDeque},
+    error::Error,
+    io::ErrorKind,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, RwLock, atomic::{AtomicBool, Ordering}},
+    time::Duration,
+};
 
-use rustmine_lib::game_profile::GameProfile;
-use tokio::{net::TcpStream, sync::Mutex};
+use rustmine_lib::{
+    common::configuration_state::ConfigKnownPackEntry, component::Component, game_profile::GameProfile, text,
+    translation,
+};
+use tokio::{
+    net::{TcpStream, tcp::OwnedReadHalf},
+    sync::{Mutex, oneshot::{self, error::TryRecvError}},
+    task,
+};
+use tokio_util::{codec::FramedRead, sync::CancellationToken};
+use uuid::Uuid;
 
 use crate::{
-    dispatch_packet_event, packet::{
-        self, serverbound::{
-            self, configuration::{self, ClientInformationConfigPacket, ClientKnownPacksPacket, ConfigurationPluginMessagePacket},
+    cookie::{self, Cookie, MAX_COOKIE_SIZE, PendingCookies},
+    event::player_events::{DisconnectCause, PlayerDisconnected, ResourcePackStatus},
+    keep_alive::{self, KeepAlive},
+    packet::{
+        self, clientbound::{
+            configuration::{
+                self as clientbound_configuration, ConfigurationAddResourcePackPacket, ConfigurationCookieRequestPacket,
+                ConfigurationCustomReportDetailsPacket, ConfigurationDisconnectPacket, ConfigurationPluginMessagePacket,
+                ConfigurationRemoveResourcePackPacket, ConfigurationServerLinksPacket, ConfigurationStoreCookiePacket,
+                ConfigurationTransferPacket, ReportDetail, ServerLink,
+            },
+            login::{LoginCookieRequestPacket, LoginDisconnectPacket},
+            play::{
+                PlayAddResourcePackPacket, PlayCookieRequestPacket, PlayCustomReportDetailsPacket, PlayDisconnectPacket,
+                PlayPluginMessagePacket, PlayRemoveResourcePackPacket, PlayServerLinksPacket, PlayStoreCookiePacket,
+                PlayTransferPacket, StartConfigurationPacket,
+            },
+        }, codec::FrameCodec, encryption::CipherStream, registry::PacketDirection, writer::PacketWriter, serverbound::{
+            configuration,
             handshake::HandshakePacket,
-            login::{self, LoginAcknowledgedPacket, LoginStartPacket},
-            status::{self, StatusRequestPacket},
-        }, Packet, RawPacket
-    }, RustmineServer, Shared
+            login, play, status,
+        }, ClientboundPacket, CompressionSettings, Packet, RawPacket
+    }, plugin_channel::{self, AnnouncedChannels, Brand, PluginMessage, REGISTER_CHANNEL, UNREGISTER_CHANNEL},
+    resource_pack::{self, ResourcePackResult, ResourcePacks}, status::OnlinePlayer, RustmineServer, Shared
 };
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+tokio::task_local! {
+    // Set for the task that reads the connection, holds the address of its reader. See wait_for_answer
+    static READING: usize;
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum State {
     Handshake,
     Play,
@@ -73,9 +111,24 @@ pub struct PlayerConnection {
     pub game_profile: Shared<Option<GameProfile>>,
 
     info: Shared<PlayerClientInfo>,
-    stream: Shared<TcpStream>, // Any I/O should be handled by the player connection implementation
+    reader: Shared<FramedRead<CipherStream<OwnedReadHalf>, FrameCodec>>, // Any I/O should be handled by the player connection implementation
+    writer: PacketWriter,
+    address: Arc<RwLock<Option<SocketAddr>>>, // Replaced by the player's real address when behind a proxy
     state: Shared<State>,
-    compression_threshold: u32,
+    compression: Shared<Option<CompressionSettings>>,
+    pub(crate) keep_alive: Shared<KeepAlive>,
+    closed: CancellationToken,
+    disconnected: Arc<AtomicBool>,
+    transferred: Arc<AtomicBool>,
+    cookies: Shared<PendingCookies>,
+    resource_packs: Shared<ResourcePacks>,
+    client_channels: Shared<HashSet<String>>, // What the client registered, it only wants messages on these
+    announced_channels: Shared<AnnouncedChannels>,
+    known_packs: Shared<Vec<ConfigKnownPackEntry>>, // From the client's answer to Select Known Packs
+    server_links: Shared<Option<Vec<ServerLink>>>, // None until set for this player, the config's are used until then
+    report_details: Shared<Option<Vec<ReportDetail>>>, // Same as server_links
+    unread: Shared<VecDeque<Arc<dyn Packet>>>, // Read while waiting on an answer, returned by read_packet first
+    reconfiguring: Shared<Option<Vec<oneshot::Sender<()>>>>, // Set from Start Configuration until back in Play
 }
 
 #[allow(dead_code)]
@@ -87,39 +140,140 @@ pub struct Player {
 impl PlayerConnection {
     /// Creates a new [`PlayerConnection`].
     pub(crate) fn new(stream: TcpStream, server: &Shared<RustmineServer>) -> Self {
+        let address = stream.peer_addr().ok();
+        let (read_half, write_half) = stream.into_split();
+        let closed = CancellationToken::new();
+
         Self {
             info: Arc::new(Mutex::new(PlayerClientInfo::default())),
-            stream: Arc::new(Mutex::new(stream)),
+            address: Arc::new(RwLock::new(address)),
+            reader: Arc::new(Mutex::new(FramedRead::new(CipherStream::new(read_half), FrameCodec))),
+            writer: PacketWriter::spawn(CipherStream::new(write_half), closed.clone()),
             server: Arc::clone(server),
             state: Arc::new(Mutex::new(State::Handshake)),
             game_profile: Arc::new(Mutex::new(None)),
-            compression_threshold: 0,
+            compression: Arc::new(Mutex::new(None)),
+            keep_alive: Arc::new(Mutex::new(KeepAlive::default())),
+            closed,
+            disconnected: Arc::new(AtomicBool::new(false)),
+            transferred: Arc::new(AtomicBool::new(false)),
+            cookies: Arc::new(Mutex::new(PendingCookies::default())),
+            resource_packs: Arc::new(Mutex::new(ResourcePacks::default())),
+            client_channels: Arc::new(Mutex::new(HashSet::new())),
+            announced_channels: Arc::new(Mutex::new(AnnouncedChannels::default())),
+            known_packs: Arc::new(Mutex::new(Vec::new())),
+            server_links: Arc::new(Mutex::new(None)),
+            report_details: Arc::new(Mutex::new(None)),
+            unread: Arc::new(Mutex::new(VecDeque::new())),
+            reconfiguring: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// Reads the next packet, answers to our keep alives and cookie requests are handled here and never returned.
     pub async fn read_packet(&mut self) -> Result<Arc<dyn Packet>, Box<std::io::Error>> {
+        if let Some(packet) = self.unread.lock().await.pop_front() {
+            return Ok(packet);
+        }
+
+        self.read_packet_from_stream().await
+    }
+
+    async fn read_packet_from_stream(&mut self) -> Result<Arc<dyn Packet>, Box<std::io::Error>> {
+        loop {
+            if let Some(packet) = self.read_unhandled_packet().await? {
+                return Ok(packet);
+            }
+        }
+    }
+
+    // Reads a single packet, `None` if it was one of those handled here
+    async fn read_unhandled_packet(&mut self) -> Result<Option<Arc<dyn Packet>>, Box<std::io::Error>> {
+        let packet = self.read_any_packet().await?;
+
+        let any = packet.as_ref() as &dyn Any;
+        let keep_alive_id = any
+            .downcast_ref::<configuration::ConfigurationKeepAlivePacket>()
+            .map(|p| p.keep_alive_id)
+            .or_else(|| any.downcast_ref::<play::PlayKeepAlivePacket>().map(|p| p.keep_alive_id));
+        let resource_pack_response = resource_pack::resource_pack_response(any);
+        let plugin_message = plugin_channel::plugin_message(any)
+            .map(|(channel, data)| (channel.to_string(), data.clone()));
+
+        if let Some(keep_alive_id) = keep_alive_id {
+            self.keep_alive.lock().await.acknowledge(keep_alive_id)?;
+            return Ok(None);
+        }
+
+        if let Some((key, payload)) = cookie::cookie_response(any) {
+            if payload.is_some_and(|payload| payload.len() > MAX_COOKIE_SIZE) {
+                return Err(Box::new(std::io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Cookie {} is larger than {} bytes", key, MAX_COOKIE_SIZE),
+                )));
+            }
+
+            if self.cookies.lock().await.resolve(key, payload) {
+                return Ok(None);
+            }
+        }
+
+        // Always handled here, the configuration handler doesn't expect these in between its own packets
+        if let Some((id, result)) = resource_pack_response {
+            let event_bus = self.server.lock().await.event_bus.clone();
+            event_bus
+                .dispatch(&Arc::new(ResourcePackStatus {
+                    player_connection: Mutex::new(self.clone()),
+                    id,
+                    result,
+                }))
+                .await;
+
+            // Only once the listeners are done, whoever waits on the pack goes on with them finished
+            self.resource_packs.lock().await.update(id, result);
+            return Ok(None);
+        }
+
+        // Same for plugin messages, mods send them whenever they like
+        if let Some((channel, data)) = plugin_message {
+            self.handle_plugin_message(&channel, data).await?;
+            return Ok(None);
+        }
+
+        // And for client information, configuration doesn't wait on it
+        let client_info_packet =
+            packet::downcast_packet::<configuration::ClientInformationConfigPacket>(packet.clone());
+        if let Ok(client_info_packet) = client_info_packet {
+            self.update_client_info(client_info_packet).await;
+            return Ok(None);
+        }
+
+        Ok(Some(packet))
+    }
+
+    async fn read_any_packet(&mut self) -> Result<Arc<dyn Packet>, Box<std::io::Error>> {
         let (_, id, buffer) = self.read_packet_raw().await.map_err(|e| {
+            // Keep the kind of I/O errors, a closed connection isn't invalid data
+            let kind = e.downcast_ref::<std::io::Error>().map_or(ErrorKind::InvalidData, |e| e.kind());
             Box::new(std::io::Error::new(
-                ErrorKind::InvalidData,
+                kind,
                 format!("Error occured whilst reading data: {}", e),
             ))
         })?;
 
-        let packet = match *self.state.lock().await {
-            State::Handshake => match id {
-                0 => packet::upcast_packet(HandshakePacket::read_from(id, buffer).await),
-                _ => Err(Box::new(std::io::Error::new(
-                    ErrorKind::InvalidData,
-                    "Packet length cannot be zero",
-                )))?,
-            },
-            State::Status => serverbound::status::read_packet(id, buffer).await,
-            State::Login => serverbound::login::read_packet(id, buffer).await,
-            State::Configuration => serverbound::configuration::read_packet(id, buffer).await,
-            State::Play => todo!(),
-            State::Transfer => todo!(),
-        }
-        .map(|boxed| Arc::from(boxed) as Arc<dyn Packet>);
+        let state = {
+            let state_lock = self.state.lock().await;
+            state_lock.clone()
+        };
+
+        let (packet_registry, event_bus) = {
+            let server = self.server.lock().await;
+            (server.packet_registry.clone(), server.event_bus.clone())
+        };
+
+        let packet = packet_registry
+            .decode(&state, PacketDirection::Serverbound, id, buffer)
+            .await
+            .map(|boxed| Arc::from(boxed) as Arc<dyn Packet>);
 
         if let Ok(ref p) = packet {
             if p.packet_id() != id {
@@ -129,58 +283,80 @@ impl PlayerConnection {
                 )));
             }
 
-            let state = {
-                let state_lock = self.state.lock().await;
-                state_lock.clone()
-            };
-
-            dispatch_packet_event! {
-                packet = p.clone(),
-                server = self.server,
-                state = state,
-                connection = Mutex::new(self.clone()),
-                table = {
-                    State::Handshake => [HandshakePacket],
-                    State::Status => [StatusRequestPacket],
-                    State::Login => [LoginStartPacket, LoginAcknowledgedPacket],
-                    State::Configuration => [ClientInformationConfigPacket, ClientKnownPacksPacket, ConfigurationPluginMessagePacket]
-                }
-            }; // Maybe centralize this into a packet registry instead of defining a table?
-
-            
+            packet_registry
+                .dispatch(&state, PacketDirection::Serverbound, p.clone(), self.clone(), event_bus)
+                .await;
         }
 
         return packet.map_err(|op| {
+            // Play relies on Unsupported to skip packets it can't decode, anything else means the packet was malformed
+            let kind = match op.kind() {
+                ErrorKind::Unsupported => ErrorKind::Unsupported,
+                _ => ErrorKind::InvalidData,
+            };
             Box::new(std::io::Error::new(
-                ErrorKind::InvalidData,
+                kind,
                 format!("Failed to read packet: {}", op),
             ))
         });
     }
 
     pub async fn read_packet_raw(&mut self) -> Result<RawPacket, Box<dyn Error>> {
-        packet::read_packet(&mut *self.stream.lock().await, self.compression_threshold).await
+        let compression = *self.compression.lock().await;
+        let mut reader = self.reader.lock().await;
+
+        tokio::select! {
+            _ = self.closed.cancelled() => Err(Box::new(std::io::Error::new(
+                ErrorKind::ConnectionAborted,
+                "Connection closed",
+            ))),
+            result = packet::read_packet(&mut *reader, compression) => result,
+        }
     }
 
     pub async fn handle_handshake(
         &mut self,
         handshake: Arc<HandshakePacket>,
-    ) -> Result<(), Box<dyn Error>> {
+    ) -> Result<(), Box<std::io::Error>> {
+        // Whoever handles the handshake reads the connection from then on
+        READING.scope(self.reader_id(), self.handle_next_state(handshake)).await
+    }
+
+    async fn handle_next_state(&mut self, handshake: Arc<HandshakePacket>) -> Result<(), Box<std::io::Error>> {
         match handshake.next_state {
             State::Status => {
                 *self.state.lock().await = State::Status;
-                status::handle_status_request(self).await?;
+                status::handle_status_request(self, &handshake).await?;
             }
-            State::Login => {
-                *self.state.lock().await = State::Login;
-                if login::handle_login(self).await.is_ok() {
-                    *self.state.lock().await = State::Configuration;
-                    if configuration::handle_configuration(self).await.is_ok() {
-                        *self.state.lock().await = State::Play;
+            State::Login | State::Transfer => {
+                *self.state.lock().await = State::Login; // A transfer is a login from another server
 
-                        
+                if handshake.next_state == State::Transfer {
+                    self.transferred.store(true, Ordering::SeqCst);
+
+                    let accepts_transfers = self.server.lock().await.config.accepts_transfers;
+                    if !accepts_transfers {
+                        let reason = translation!("multiplayer.disconnect.transfers_disabled");
+                        self.disconnect_with(Some(reason), DisconnectCause::Error("Transfers are disabled".to_string())).await;
+                        return Ok(());
                     }
                 }
+
+                login::handle_login(self, &handshake).await?;
+
+                *self.state.lock().await = State::Configuration;
+                task::spawn(keep_alive::keep_alive_loop(self.clone()));
+                configuration::handle_configuration(self).await?;
+
+                *self.state.lock().await = State::Play;
+                self.keep_alive.lock().await.set_paused(false);
+
+                // Counted in the server list for as long as they're in Play
+                let online_player = self.online_player().await?;
+                self.server.lock().await.add_online_player(online_player.clone());
+                let result = play::handle_play(self).await;
+                self.server.lock().await.remove_online_player(&online_player.uuid);
+                result?;
             }
             _ => {
                 return Err(Box::new(std::io::Error::new(
@@ -193,33 +369,30 @@ impl PlayerConnection {
         Ok(())
     }
 
-    pub async fn write_packet(&mut self, packet: &dyn Packet) -> Result<(), Box<std::io::Error>> {
-        packet::write_packet(
-            packet,
-            &mut *self.stream.lock().await,
-            self.compression_threshold,
-        )
-        .await
-        .map_err(|e| Box::new(e))
+    /// Queues `packet` to be sent, only waits when the outbound queue is full.
+    pub async fn write_packet(&mut self, packet: &dyn ClientboundPacket) -> Result<(), Box<std::io::Error>> {
+        let compression = *self.compression.lock().await;
+        let frame = packet::encode_packet(packet, compression)?;
+        self.writer.send(frame).await.map_err(Box::new)
+    }
+
+    /// Like [`Self::write_packet`] but never waits, fails with [`ErrorKind::WouldBlock`] when the queue is full.
+    pub async fn try_write_packet(&mut self, packet: &dyn ClientboundPacket) -> Result<(), Box<std::io::Error>> {
+        let compression = *self.compression.lock().await;
+        let frame = packet::encode_packet(packet, compression)?;
+        self.writer.try_send(frame).map_err(Box::new)
+    }
+
+    /// Waits until every packet queued so far has been written to the socket.
+    pub async fn flush(&self) -> Result<(), Box<std::io::Error>> {
+        self.writer.flush().await.map_err(Box::new)
     }
 
     pub(crate) async fn update_client_info(
         &mut self,
         client_info_packet: Arc<configuration::ClientInformationConfigPacket>,
-        config_plugin_message: Arc<configuration::ConfigurationPluginMessagePacket>,
-    ) -> Result<(), Box<std::io::Error>> {
-        if config_plugin_message.channel != "minecraft:brand" {
-            return Err(Box::new(std::io::Error::new(
-                ErrorKind::InvalidData,
-                "Expected minecraft:brand channel",
-            )));
-        }
-
-        let brand = String::from_utf8(config_plugin_message.data.clone())
-            .map_err(|_| std::io::Error::new(ErrorKind::InvalidData, "Invalid brand data"))?;
-
+    ) {
         let mut info = self.info.lock().await;
-        info.brand = brand;
         info.locale = client_info_packet.locale.clone();
         info.view_distance = client_info_packet.view_distance;
         info.chat_mode = client_info_packet.chat_mode;
@@ -228,12 +401,696 @@ impl PlayerConnection {
         info.main_hand = client_info_packet.main_hand as u8;
         info.text_filtering = client_info_packet.text_filtering;
         info.server_listing = client_info_packet.server_listing;
+    }
+
+    // The brand and channel lists are kept on the connection, anything else goes to whoever registered the channel
+    async fn handle_plugin_message(&mut self, channel: &str, data: Vec<u8>) -> Result<(), Box<std::io::Error>> {
+        match channel {
+            plugin_channel::BRAND_CHANNEL => {
+                let brand = Brand::decode(&data)
+                    .map_err(|_| std::io::Error::new(ErrorKind::InvalidData, "Invalid brand data"))?;
+                self.info.lock().await.brand = brand.0;
+            }
+            REGISTER_CHANNEL => {
+                self.client_channels
+                    .lock()
+                    .await
+                    .extend(plugin_channel::parse_channel_list(&data));
+            }
+            UNREGISTER_CHANNEL => {
+                let mut client_channels = self.client_channels.lock().await;
+                for channel in plugin_channel::parse_channel_list(&data) {
+                    client_channels.remove(&channel);
+                }
+            }
+            _ => {
+                let (channel_registry, event_bus) = {
+                    let server = self.server.lock().await;
+                    (server.channel_registry.clone(), server.event_bus.clone())
+                };
+
+                channel_registry.dispatch(channel, data, self.clone(), event_bus).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Tells the client about channels registered or unregistered since it was last told, a no-op otherwise
+    pub(crate) async fn sync_plugin_channels(&mut self) -> Result<(), Box<std::io::Error>> {
+        let channel_registry = self.server.lock().await.channel_registry.clone();
+        let version = channel_registry.version();
+
+        if self.announced_channels.lock().await.version == Some(version) {
+            return Ok(());
+        }
+
+        let channels = channel_registry.channels().await;
+        let (registered, unregistered) = {
+            let mut announced = self.announced_channels.lock().await;
+            let registered: Vec<String> = channels
+                .iter()
+                .filter(|channel| !announced.channels.contains(channel))
+                .cloned()
+                .collect();
+            let unregistered: Vec<String> = announced
+                .channels
+                .iter()
+                .filter(|channel| !channels.contains(channel))
+                .cloned()
+                .collect();
+
+            announced.version = Some(version);
+            announced.channels = channels;
+            (registered, unregistered)
+        };
+
+        if !unregistered.is_empty() {
+            self.send_plugin_message_raw(UNREGISTER_CHANNEL, plugin_channel::channel_list(&unregistered))
+                .await?;
+        }
+        if !registered.is_empty() {
+            self.send_plugin_message_raw(REGISTER_CHANNEL, plugin_channel::channel_list(&registered))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Switches the connection over to AES/CFB8, everything read or written after this is encrypted.
+    pub(crate) async fn enable_encryption(&mut self, shared_secret: &[u8]) -> Result<(), Box<std::io::Error>> {
+        self.writer.enable_encryption(shared_secret).await?;
+        self.reader
+            .lock()
+            .await
+            .get_mut()
+            .enable_encryption(shared_secret)
+            .map_err(Box::new)
+    }
+
+    /// Every packet read or written after this uses the compressed format.
+    pub(crate) async fn enable_compression(&mut self, settings: CompressionSettings) {
+        *self.compression.lock().await = Some(settings);
+    }
+
+    async fn online_player(&self) -> Result<OnlinePlayer, Box<std::io::Error>> {
+        let profile = self.game_profile.lock().await.clone().ok_or_else(|| {
+            Box::new(std::io::Error::new(ErrorKind::InvalidData, "Player has no game profile"))
+        })?;
+
+        Ok(OnlinePlayer {
+            username: profile.username,
+            uuid: profile.uuid,
+            listed: self.info.lock().await.server_listing,
+        })
+    }
+
+    pub async fn state(&self) -> State {
+        self.state.lock().await.clone()
+    }
+
+    /// What the client told us about itself, the brand included once it has been sent.
+    pub async fn client_info(&self) -> PlayerClientInfo {
+        self.info.lock().await.clone()
+    }
+
+    /// The packs the client said it has, empty until configuration gets that far.
+    pub async fn known_packs(&self) -> Vec<ConfigKnownPackEntry> {
+        self.known_packs.lock().await.clone()
+    }
+
+    pub(crate) async fn set_known_packs(&mut self, known_packs: Vec<ConfigKnownPackEntry>) {
+        *self.known_packs.lock().await = known_packs;
+    }
+
+    /// The round trip time measured through keep alives, `None` until the first one is answered.
+    pub async fn latency(&self) -> Option<Duration> {
+        self.keep_alive.lock().await.latency()
+    }
+
+    /// Whether the client came here through a Transfer packet sent by another server.
+    pub fn is_transferred(&self) -> bool {
+        self.transferred.load(Ordering::SeqCst)
+    }
+
+    /// Sends the client to another server, it disconnects from this one on its own.
+    pub async fn transfer(&mut self, host: &str, port: u16) -> Result<(), Box<std::io::Error>> {
+        let (host, port) = (host.to_string(), port as u32);
+
+        match self.state().await {
+            State::Configuration => self.write_packet(&ConfigurationTransferPacket { host, port }).await,
+            State::Play => self.write_packet(&PlayTransferPacket { host, port }).await,
+            state => Err(Box::new(std::io::Error::new(
+                ErrorKind::Unsupported,
+                format!("Can't transfer a client in {:?}", state),
+            ))),
+        }
+    }
+
+    //
+    // Sends a Play connection back through configuration, to sync new registry data or resource packs, and returns
+    // it to Play without reconnecting. From the task reading the connection the acknowledgement is read here,
+    // anywhere else handle_play reads it and runs configuration while this waits. Calls made while one is already
+    // under way wait for that one instead. Play packets sent before the client acknowledges are dropped, their
+    // events have already fired by then.
+    //
+
+    pub async fn reconfigure(&mut self) -> Result<(), Box<std::io::Error>> {
+        let (sender, done) = oneshot::channel();
+
+        {
+            let reconfiguring = self.reconfiguring.clone();
+            let mut reconfiguring = reconfiguring.lock().await;
+
+            match reconfiguring.as_mut() {
+                Some(waiting) => waiting.push(sender),
+                None => {
+                    let state = self.state().await;
+                    if state != State::Play {
+                        return Err(Box::new(std::io::Error::new(
+                            ErrorKind::Unsupported,
+                            format!("Can't reconfigure a client in {:?}", state),
+                        )));
+                    }
+
+                    // A Play keep alive after Start Configuration would reach the client in the wrong state
+                    self.keep_alive.lock().await.set_paused(true);
+                    self.write_packet(&StartConfigurationPacket).await?;
+                    *reconfiguring = Some(vec![sender]);
+                }
+            }
+        }
+
+        if self.is_reading() {
+            self.wait_for_packet::<play::ConfigurationAcknowledgedPacket>().await?;
+            self.run_reconfiguration().await?;
+        }
 
+        self.wait_for_answer(done).await
+    }
+
+    // Called once the client acknowledged Start Configuration, does nothing if it wasn't asked to
+    pub(crate) async fn run_reconfiguration(&mut self) -> Result<(), Box<std::io::Error>> {
+        if self.reconfiguring.lock().await.is_none() {
+            return Ok(());
+        }
+
+        *self.state.lock().await = State::Configuration;
+        self.keep_alive.lock().await.set_paused(false);
+        let result = configuration::configure(self, true).await;
+
+        // Paused again by configure, same as after the first configuration
+        if result.is_ok() {
+            *self.state.lock().await = State::Play;
+            self.keep_alive.lock().await.set_paused(false);
+        }
+
+        // Dropping them on an error tells whoever waits that it failed
+        let waiting = self.reconfiguring.lock().await.take().unwrap_or_default();
+        result?;
+
+        for sender in waiting {
+            let _ = sender.send(());
+        }
         Ok(())
     }
-    
+
+    /// Reads until the client sends a `P`. Whatever it sends before that goes through read_packet and its events
+    /// as usual, but isn't returned to anyone.
+    pub async fn wait_for_packet<P: Packet>(&mut self) -> Result<Arc<P>, Box<std::io::Error>> {
+        loop {
+            match self.read_packet().await {
+                Ok(packet) => {
+                    if let Ok(packet) = packet::downcast_packet::<P>(packet) {
+                        return Ok(packet);
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::Unsupported => {} // Same as handle_play
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Stores `cookie` on the client, serialized as JSON.
+    pub async fn store_cookie<C: Cookie>(&mut self, cookie: &C) -> Result<(), Box<std::io::Error>> {
+        let payload = serde_json::to_vec(cookie).map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))?;
+        self.store_cookie_raw(C::KEY, payload).await
+    }
+
+    /// Asks the client for its [`Cookie`], `None` if it doesn't have one.
+    pub async fn request_cookie<C: Cookie>(&mut self) -> Result<Option<C>, Box<std::io::Error>> {
+        match self.request_cookie_raw(C::KEY).await? {
+            Some(payload) => serde_json::from_slice(&payload)
+                .map(Some)
+                .map_err(|e| Box::new(std::io::Error::new(ErrorKind::InvalidData, e))),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn store_cookie_raw(&mut self, key: &str, payload: Vec<u8>) -> Result<(), Box<std::io::Error>> {
+        if payload.len() > MAX_COOKIE_SIZE {
+            return Err(Box::new(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("Cookies can't be larger than {} bytes", MAX_COOKIE_SIZE),
+            )));
+        }
+
+        let key = key.to_string();
+        match self.state().await {
+            State::Configuration => self.write_packet(&ConfigurationStoreCookiePacket { key, payload }).await,
+            State::Play => self.write_packet(&PlayStoreCookiePacket { key, payload }).await,
+            state => Err(Box::new(std::io::Error::new(
+                ErrorKind::Unsupported,
+                format!("Can't store a cookie in {:?}", state),
+            ))),
+        }
+    }
+
+    /// Asks the client for the cookie stored under `key`, `None` if it doesn't have one.
+    pub async fn request_cookie_raw(&mut self, key: &str) -> Result<Option<Vec<u8>>, Box<std::io::Error>> {
+        let answer = self.cookies.lock().await.wait_for(key);
+
+        let key = key.to_string();
+        match self.state().await {
+            State::Login => self.write_packet(&LoginCookieRequestPacket { key }).await?,
+            State::Configuration => self.write_packet(&ConfigurationCookieRequestPacket { key }).await?,
+            State::Play => self.write_packet(&PlayCookieRequestPacket { key }).await?,
+            state => {
+                return Err(Box::new(std::io::Error::new(
+                    ErrorKind::Unsupported,
+                    format!("Can't request a cookie in {:?}", state),
+                )));
+            }
+        }
+
+        self.wait_for_answer(answer).await
+    }
+
+    //
+    // Waits for an answer that read_packet resolves once the client sends it. The task reading the connection, and
+    // the listeners it calls, would wait forever on itself, so there the packets are read here until it comes in.
+    // Anything that isn't the answer is kept and handed out by read_packet afterwards. A packet is never dropped
+    // halfway through its listeners. Any other task leaves the reading to the one doing it.
+    //
+
+    async fn wait_for_answer<T>(&mut self, mut answer: oneshot::Receiver<T>) -> Result<T, Box<std::io::Error>> {
+        let closed = || Box::new(std::io::Error::new(ErrorKind::ConnectionAborted, "Connection closed"));
+
+        if !self.is_reading() {
+            return tokio::select! {
+                biased;
+                result = answer => result.map_err(|_| closed()),
+                _ = self.closed.cancelled() => Err(closed()),
+            };
+        }
+
+        loop {
+            match answer.try_recv() {
+                Ok(result) => return Ok(result),
+                Err(TryRecvError::Closed) => return Err(closed()),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            match self.read_unhandled_packet().await {
+                Ok(Some(packet)) => self.unread.lock().await.push_back(packet),
+                Ok(None) => {}
+                Err(e) if e.kind() == ErrorKind::Unsupported => {} // Same as handle_play
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Sends a resource pack for the client to download and apply, returning the id it's tracked by.
+    /// `hash` is the SHA-1 of the pack as hex, or empty to skip the check. A forced pack can't be declined
+    /// without leaving the server.
+    pub async fn push_resource_pack(
+        &mut self,
+        url: &str,
+        hash: &str,
+        forced: bool,
+        prompt: Component,
+    ) -> Result<Uuid, Box<std::io::Error>> {
+        resource_pack::validate(url, hash)?;
+
+        let id = Uuid::new_v4();
+        let (url, hash, prompt) = (url.to_string(), hash.to_string(), Some(prompt));
+
+        // Tracked before sending so an answer can't arrive for a pack we don't know about
+        self.resource_packs.lock().await.pushed(id);
+
+        match self.state().await {
+            State::Configuration => {
+                self.write_packet(&ConfigurationAddResourcePackPacket { id, url, hash, forced, prompt }).await?
+            }
+            State::Play => self.write_packet(&PlayAddResourcePackPacket { id, url, hash, forced, prompt }).await?,
+            state => {
+                return Err(Box::new(std::io::Error::new(
+                    ErrorKind::Unsupported,
+                    format!("Can't push a resource pack in {:?}", state),
+                )));
+            }
+        }
+
+        Ok(id)
+    }
+
+    /// Removes a pack pushed earlier, or every pack with `None`.
+    pub async fn pop_resource_pack(&mut self, id: Option<Uuid>) -> Result<(), Box<std::io::Error>> {
+        match self.state().await {
+            State::Configuration => self.write_packet(&ConfigurationRemoveResourcePackPacket { id }).await,
+            State::Play => self.write_packet(&PlayRemoveResourcePackPacket { id }).await,
+            state => Err(Box::new(std::io::Error::new(
+                ErrorKind::Unsupported,
+                format!("Can't pop a resource pack in {:?}", state),
+            ))),
+        }
+    }
+
+    /// The last answer the client gave about the pack, `None` if it hasn't answered yet.
+    pub async fn resource_pack_status(&self, id: &Uuid) -> Option<ResourcePackResult> {
+        self.resource_packs.lock().await.status(id)
+    }
+
+    /// Waits until the client is done with the pack, whether it loaded or not. Called from a configuration listener
+    /// this holds configuration back until then.
+    pub async fn wait_for_resource_pack(&mut self, id: Uuid) -> Result<ResourcePackResult, Box<std::io::Error>> {
+        let answer = self.resource_packs.lock().await.wait_for(id);
+        self.wait_for_answer(answer).await
+    }
+
+    // Whether this is the task reading the connection, or a listener it's awaiting
+    fn is_reading(&self) -> bool {
+        READING.try_with(|reader| *reader == self.reader_id()).unwrap_or(false)
+    }
+
+    fn reader_id(&self) -> usize {
+        Arc::as_ptr(&self.reader) as usize
+    }
+
+    pub async fn send_plugin_message<M: PluginMessage>(&mut self, message: &M) -> Result<(), Box<std::io::Error>> {
+        self.send_plugin_message_raw(M::CHANNEL, message.encode()).await
+    }
+
+    pub async fn send_plugin_message_raw(&mut self, channel: &str, data: Vec<u8>) -> Result<(), Box<std::io::Error>> {
+        let channel = channel.to_string();
+        match self.state().await {
+            State::Configuration => self.write_packet(&ConfigurationPluginMessagePacket { channel, data }).await,
+            State::Play => self.write_packet(&PlayPluginMessagePacket { channel, data }).await,
+            state => Err(Box::new(std::io::Error::new(
+                ErrorKind::Unsupported,
+                format!("Can't send a plugin message in {:?}", state),
+            ))),
+        }
+    }
+
+    /// Whether the client registered `channel` through minecraft:register.
+    pub async fn listens_on(&self, channel: &str) -> bool {
+        self.client_channels.lock().await.contains(channel)
+    }
+
+    /// The links shown in this player's pause menu.
+    pub async fn server_links(&self) -> Vec<ServerLink> {
+        if let Some(links) = self.server_links.lock().await.clone() {
+            return links;
+        }
+        self.server.lock().await.config.server_links.clone()
+    }
+
+    /// Replaces the player's links, sent right away once the client is past login.
+    pub async fn set_server_links(&mut self, links: Vec<ServerLink>) -> Result<(), Box<std::io::Error>> {
+        *self.server_links.lock().await = Some(links.clone());
+
+        match self.state().await {
+            State::Configuration => self.write_packet(&ConfigurationServerLinksPacket { links }).await,
+            State::Play => self.write_packet(&PlayServerLinksPacket { links }).await,
+            _ => Ok(()), // Sent during configuration
+        }
+    }
+
+    pub async fn report_details(&self) -> Vec<ReportDetail> {
+        if let Some(details) = self.report_details.lock().await.clone() {
+            return details;
+        }
+        self.server.lock().await.config.report_details.clone()
+    }
+
+    /// Replaces the player's report details, sent right away once the client is past login.
+    pub async fn set_report_details(&mut self, details: Vec<ReportDetail>) -> Result<(), Box<std::io::Error>> {
+        clientbound_configuration::validate_report_details(&details)?;
+        *self.report_details.lock().await = Some(details.clone());
+
+        match self.state().await {
+            State::Configuration => self.write_packet(&ConfigurationCustomReportDetailsPacket { details }).await,
+            State::Play => self.write_packet(&PlayCustomReportDetailsPacket { details }).await,
+            _ => Ok(()), // Sent during configuration
+        }
+    }
+
+    /// Kicks the player, showing them `reason`.
+    pub async fn disconnect(&mut self, reason: Component) {
+        self.disconnect_with(Some(reason), DisconnectCause::Kicked).await;
+    }
+
+    // Sends the disconnect packet for the current state if there is a reason, then closes the connection.
+    // Only the first call does anything, so it's safe to call when the connection might already be closing.
+    pub(crate) async fn disconnect_with(&mut self, reason: Option<Component>, cause: DisconnectCause) {
+        if self.disconnected.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let state = self.state().await;
+        if let Some(reason) = reason.clone() {
+            let _ = match state {
+                State::Login => self.write_packet(&LoginDisconnectPacket { reason }).await,
+                State::Configuration => self.write_packet(&ConfigurationDisconnectPacket { reason }).await,
+                State::Play => self.write_packet(&PlayDisconnectPacket { reason }).await,
+                _ => Ok(()), // Nothing to tell a client that is only pinging
+            };
+        }
+
+        self.close();
+
+        if matches!(state, State::Login | State::Configuration | State::Play) {
+            let event_bus = self.server.lock().await.event_bus.clone();
+            event_bus
+                .dispatch(&Arc::new(PlayerDisconnected {
+                    player_connection: Mutex::new(self.clone()),
+                    reason,
+                    cause,
+                }))
+                .await;
+        }
+    }
+
+    // Turns whatever ended the connection into a disconnect, a client that left on its own isn't told why
+    pub(crate) async fn disconnect_after_error(&mut self, error: &std::io::Error) {
+        match error.kind() {
+            ErrorKind::UnexpectedEof
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe => self.disconnect_with(None, DisconnectCause::ConnectionLost).await,
+            ErrorKind::PermissionDenied => {
+                // Refusals are worded for the player already
+                let reason = text!(error.to_string());
+                self.disconnect_with(Some(reason), DisconnectCause::Error(error.to_string())).await
+            }
+            _ => {
+                let reason = text!(format!("Disconnected: {}", error));
+                self.disconnect_with(Some(reason), DisconnectCause::Error(error.to_string())).await
+            }
+        }
+    }
+
+    /// Stops reading and lets the writer finish whatever is already queued, after which the socket is closed.
+    pub fn close(&self) {
+        self.closed.cancel();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.is_cancelled()
+    }
+
+    pub(crate) fn closed(&self) -> CancellationToken {
+        self.closed.clone()
+    }
+
+    pub fn address(&self) -> Option<SocketAddr> {
+        *self.address.read().unwrap()
+    }
+
+    // Keeps the port, proxies only forward the IP
+    pub(crate) fn set_address(&self, ip: IpAddr) {
+        let mut address = self.address.write().unwrap();
+        let port = address.map_or(0, |address| address.port());
+        *address = Some(SocketAddr::new(ip, port));
+    }
+
     pub(crate) async fn set_game_profile(&mut self, profile: GameProfile) {
         let mut profile_player = self.game_profile.lock().await;
         *profile_player = Some(profile);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustmine_lib::data;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use crate::{config::ServerConfig, packet::clientbound};
+
+    // A connection in Play on the server's end, and the client's end of it
+    async fn connect() -> (PlayerConnection, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+
+        let cnx = PlayerConnection::new(stream, &RustmineServer::new(ServerConfig::default()));
+        *cnx.state.lock().await = State::Play;
+        (cnx, client)
+    }
+
+    async fn send(client: &mut TcpStream, id: u32, data: &[u8]) {
+        let mut body = Vec::new();
+        data::write_varint(&mut body, id);
+        body.extend_from_slice(data);
+
+        let mut frame = Vec::new();
+        data::write_varint(&mut frame, body.len() as u32);
+        frame.extend(body);
+        client.write_all(&frame).await.unwrap();
+    }
+
+    // Skips whatever the server sent first, so the answer can't come before the question
+    async fn skip_frame(client: &mut TcpStream) {
+        let mut length = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = client.read_u8().await.unwrap();
+            length |= ((byte & 0x7F) as u32) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        client.read_exact(&mut vec![0; length as usize]).await.unwrap();
+    }
+
+    async fn send_cookie(client: &mut TcpStream, key: &str, payload: &[u8]) {
+        let mut data = Vec::new();
+        data::write_string(&mut data, key);
+        data::write_bool(&mut data, true);
+        data::write_varint(&mut data, payload.len() as u32);
+        data.extend_from_slice(payload);
+        send(client, play::PlayCookieResponsePacket::id(), &data).await;
+    }
+
+    #[tokio::test]
+    async fn reads_the_cookie_itself_in_the_reading_task() {
+        let (mut cnx, mut client) = connect().await;
+
+        let client_task = tokio::spawn(async move {
+            skip_frame(&mut client).await;
+            send(&mut client, play::ClientTickEndPacket::id(), &[]).await;
+            send_cookie(&mut client, "test:cookie", b"answer").await;
+            client
+        });
+
+        let reader_id = cnx.reader_id();
+        let (payload, unread) = READING
+            .scope(reader_id, async {
+                let payload = cnx.request_cookie_raw("test:cookie").await.unwrap();
+                (payload, cnx.read_packet().await.unwrap())
+            })
+            .await;
+
+        assert_eq!(payload, Some(b"answer".to_vec()));
+        assert!(packet::downcast_packet::<play::ClientTickEndPacket>(unread).is_ok());
+        client_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_resource_pack_listeners() {
+        let (mut cnx, mut client) = connect().await;
+
+        // Takes a while, the pack mustn't count as done before it is
+        let finished = Arc::new(AtomicBool::new(false));
+        let event_bus = cnx.server.lock().await.event_bus.clone();
+        event_bus
+            .listen(false, {
+                let finished = finished.clone();
+                move |event: Arc<ResourcePackStatus>| {
+                    let finished = finished.clone();
+                    async move {
+                        if event.result.is_final() {
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            finished.store(true, Ordering::SeqCst);
+                        }
+                        None::<()>
+                    }
+                }
+            })
+            .await;
+
+        let id = cnx.push_resource_pack("https://example.com/pack.zip", "", false, text!("")).await.unwrap();
+
+        let mut response = Vec::new();
+        data::write_uuid(&mut response, &id);
+        data::write_varint(&mut response, 0); // Successfully loaded
+        skip_frame(&mut client).await;
+        send(&mut client, play::PlayResourcePackResponsePacket::id(), &response).await;
+
+        let reader_id = cnx.reader_id();
+        let result = READING.scope(reader_id, cnx.wait_for_resource_pack(id)).await.unwrap();
+
+        assert!(result.is_final());
+        assert!(finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn reconfigures_through_handle_play() {
+        let (cnx, mut client) = connect().await;
+
+        // Only Finish Configuration is left to answer
+        let configuration_tasks = cnx.server.lock().await.configuration_tasks.clone();
+        for name in configuration_tasks.names().await {
+            configuration_tasks.remove(&name).await;
+        }
+
+        let mut reader = cnx.clone();
+        let reader_id = cnx.reader_id();
+        let reading = tokio::spawn(READING.scope(reader_id, async move { play::handle_play(&mut reader).await }));
+
+        let mut requester = cnx.clone();
+        let reconfigure = tokio::spawn(async move { requester.reconfigure().await });
+
+        skip_frame(&mut client).await; // Start Configuration
+        send(&mut client, play::ConfigurationAcknowledgedPacket::id(), &[]).await;
+        skip_frame(&mut client).await; // Finish Configuration
+        send(&mut client, clientbound::FinishConfigurationPacket::id(), &[]).await;
+
+        reconfigure.await.unwrap().unwrap();
+        assert_eq!(cnx.state().await, State::Play);
+        assert!(!cnx.keep_alive.lock().await.is_paused());
+        reading.abort();
+    }
+
+    #[tokio::test]
+    async fn leaves_the_reading_to_the_reading_task() {
+        let (mut cnx, mut client) = connect().await;
+
+        let mut requester = cnx.clone();
+        let request = tokio::spawn(async move { requester.request_cookie_raw("test:cookie").await });
+
+        skip_frame(&mut client).await;
+        send_cookie(&mut client, "test:cookie", b"answer").await;
+        send(&mut client, play::ClientTickEndPacket::id(), &[]).await;
+
+        // The cookie is picked up on the way to the next packet, which still reaches read_packet
+        let reader_id = cnx.reader_id();
+        let packet = READING.scope(reader_id, cnx.read_packet()).await.unwrap();
+        assert!(packet::downcast_packet::<play::ClientTickEndPacket>(packet).is_ok());
+        assert_eq!(request.await.unwrap().unwrap(), Some(b"answer".to_vec()));
+    }
+}
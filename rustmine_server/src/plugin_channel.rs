@@ -0,0 +1,328 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    io::{Error, ErrorKind},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use rustmine_lib::data;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::{
+    event::{player_events::PluginMessageReceived, EventBus},
+    packet::serverbound::{configuration::ConfigurationPluginMessagePacket, play::PlayPluginMessagePacket},
+    player::PlayerConnection,
+};
+
+pub const BRAND_CHANNEL: &str = "minecraft:brand";
+pub const REGISTER_CHANNEL: &str = "minecraft:register";
+pub const UNREGISTER_CHANNEL: &str = "minecraft:unregister";
+
+//
+// Plugin messages are raw bytes sent over a namespaced channel, in Configuration or Play. A channel registered here
+// decodes its messages into a typed PluginMessage and fires PluginMessageReceived for it. Messages on channels
+// nobody registered are dropped, mods send plenty of those.
+//
+
+pub trait PluginMessage: Send + Sync + Sized + 'static {
+    const CHANNEL: &'static str;
+
+    fn decode(data: &[u8]) -> Result<Self, Error>;
+    fn encode(&self) -> Vec<u8>;
+}
+
+/// The client or server software name, sent by both sides at the start of configuration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Brand(pub String);
+
+impl PluginMessage for Brand {
+    const CHANNEL: &'static str = BRAND_CHANNEL;
+
+    fn decode(data: &[u8]) -> Result<Self, Error> {
+        data::read_string(data, &mut 0).map(Brand)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data::write_string(&mut data, &self.0);
+        data
+    }
+}
+
+type MessageDispatcher = Box<
+    dyn Fn(Vec<u8>, PlayerConnection, Arc<EventBus>) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>
+        + Send
+        + Sync,
+>;
+
+#[derive(Default)]
+pub struct ChannelRegistry {
+    channels: RwLock<HashMap<String, Arc<MessageDispatcher>>>,
+    version: AtomicU64, // Bumped on every change so connections know to announce it again
+}
+
+impl ChannelRegistry {
+    /// Registers `M::CHANNEL`, replacing whatever was registered there before.
+    pub async fn register<M: PluginMessage>(&self) -> Result<(), Box<Error>> {
+        if !is_valid_channel(M::CHANNEL) || is_reserved_channel(M::CHANNEL) {
+            return Err(Box::new(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Can't register channel {}", M::CHANNEL),
+            )));
+        }
+
+        let dispatcher: MessageDispatcher = Box::new(|data, connection, event_bus| {
+            Box::pin(async move {
+                let event = Arc::new(PluginMessageReceived::<M> {
+                    message: M::decode(&data)?,
+                    player_connection: Mutex::new(connection),
+                });
+
+                event_bus.dispatch(&event).await;
+                Ok(())
+            })
+        });
+
+        self.channels
+            .write()
+            .await
+            .insert(M::CHANNEL.to_string(), Arc::new(dispatcher));
+        self.version.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Returns false if `channel` wasn't registered.
+    pub async fn unregister(&self, channel: &str) -> bool {
+        let removed = self.channels.write().await.remove(channel).is_some();
+        if removed {
+            self.version.fetch_add(1, Ordering::SeqCst);
+        }
+        removed
+    }
+
+    pub async fn is_registered(&self, channel: &str) -> bool {
+        self.channels.read().await.contains_key(channel)
+    }
+
+    /// Every registered channel, sorted.
+    pub async fn channels(&self) -> Vec<String> {
+        let mut channels: Vec<String> = self.channels.read().await.keys().cloned().collect();
+        channels.sort();
+        channels
+    }
+
+    pub(crate) fn version(&self) -> u64 {
+        self.version.load(Ordering::SeqCst)
+    }
+
+    // Returns false when nobody registered the channel
+    pub(crate) async fn dispatch(
+        &self,
+        channel: &str,
+        data: Vec<u8>,
+        connection: PlayerConnection,
+        event_bus: Arc<EventBus>,
+    ) -> Result<bool, Box<Error>> {
+        let Some(dispatcher) = self.channels.read().await.get(channel).cloned() else {
+            return Ok(false);
+        };
+
+        dispatcher(data, connection, event_bus).await.map_err(|e| {
+            Box::new(Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid plugin message on {}: {}", channel, e),
+            ))
+        })?;
+        Ok(true)
+    }
+}
+
+// What a connection last told the client about our channels, None until it has been told anything
+#[derive(Default)]
+pub(crate) struct AnnouncedChannels {
+    pub(crate) version: Option<u64>,
+    pub(crate) channels: Vec<String>,
+}
+
+/// Whether `channel` is a valid `namespace:path` identifier.
+pub fn is_valid_channel(channel: &str) -> bool {
+    let Some((namespace, path)) = channel.split_once(':') else {
+        return false;
+    };
+
+    let valid = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-.".contains(c);
+
+    !namespace.is_empty()
+        && !path.is_empty()
+        && namespace.chars().all(valid)
+        && path.chars().all(|c| valid(c) || c == '/')
+}
+
+// Channels the server handles itself
+fn is_reserved_channel(channel: &str) -> bool {
+    matches!(channel, BRAND_CHANNEL | REGISTER_CHANNEL | UNREGISTER_CHANNEL)
+}
+
+// minecraft:register and minecraft:unregister carry channel names separated by null bytes
+pub(crate) fn channel_list(channels: &[String]) -> Vec<u8> {
+    channels.join("\0").into_bytes()
+}
+
+pub(crate) fn parse_channel_list(data: &[u8]) -> Vec<String> {
+    data.split(|byte| *byte == 0)
+        .filter_map(|channel| std::str::from_utf8(channel).ok())
+        .filter(|channel| is_valid_channel(channel))
+        .map(str::to_string)
+        .collect()
+}
+
+// The channel and data of a Plugin Message, whichever state it was sent in
+pub(crate) fn plugin_message(packet: &dyn Any) -> Option<(&str, &Vec<u8>)> {
+    if let Some(p) = packet.downcast_ref::<ConfigurationPluginMessagePacket>() {
+        return Some((&p.channel, &p.data));
+    }
+    packet
+        .downcast_ref::<PlayPluginMessagePacket>()
+        .map(|p| (p.channel.as_str(), &p.data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::{TcpListener, TcpStream};
+
+    use crate::{config::ServerConfig, RustmineServer};
+
+    struct Counter(u32);
+
+    impl PluginMessage for Counter {
+        const CHANNEL: &'static str = "test:counter";
+
+        fn decode(data: &[u8]) -> Result<Self, Error> {
+            data::read_varint(data, &mut 0).map(Counter)
+        }
+
+        fn encode(&self) -> Vec<u8> {
+            let mut data = Vec::new();
+            data::write_varint(&mut data, self.0);
+            data
+        }
+    }
+
+    struct Reserved;
+
+    impl PluginMessage for Reserved {
+        const CHANNEL: &'static str = BRAND_CHANNEL;
+
+        fn decode(_: &[u8]) -> Result<Self, Error> {
+            Ok(Reserved)
+        }
+
+        fn encode(&self) -> Vec<u8> {
+            Vec::new()
+        }
+    }
+
+    struct Invalid;
+
+    impl PluginMessage for Invalid {
+        const CHANNEL: &'static str = "Test:Invalid";
+
+        fn decode(_: &[u8]) -> Result<Self, Error> {
+            Ok(Invalid)
+        }
+
+        fn encode(&self) -> Vec<u8> {
+            Vec::new()
+        }
+    }
+
+    // Nothing is read from or written to it, dispatching only needs something to hand to the listeners
+    async fn connection() -> (PlayerConnection, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        (PlayerConnection::new(stream, &RustmineServer::new(ServerConfig::default())), client)
+    }
+
+    #[test]
+    fn validates_channel_identifiers() {
+        for channel in ["minecraft:brand", "my_mod:data/sync", "a-b.c:0_9"] {
+            assert!(is_valid_channel(channel), "{}", channel);
+        }
+        for channel in ["brand", ":brand", "minecraft:", "Minecraft:brand", "my/mod:data", "mod:da ta", "mod:über"] {
+            assert!(!is_valid_channel(channel), "{}", channel);
+        }
+    }
+
+    #[test]
+    fn round_trips_channel_lists() {
+        let channels = vec!["test:a".to_string(), "test:b/c".to_string()];
+        assert_eq!(channel_list(&channels), b"test:a\x00test:b/c");
+        assert_eq!(parse_channel_list(&channel_list(&channels)), channels);
+    }
+
+    #[test]
+    fn skips_invalid_channels_in_lists() {
+        let data = b"test:a\x00not a channel\x00\xFF\xFE\x00\x00test:b";
+        assert_eq!(parse_channel_list(data), ["test:a", "test:b"]);
+    }
+
+    #[test]
+    fn decodes_brands() {
+        let brand = Brand("vanilla".to_string());
+        assert_eq!(Brand::decode(&brand.encode()).unwrap(), brand);
+        assert!(Brand::decode(&[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn only_registers_valid_unreserved_channels() {
+        let registry = ChannelRegistry::default();
+
+        assert_eq!(registry.register::<Reserved>().await.unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(registry.register::<Invalid>().await.unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(registry.version(), 0);
+
+        registry.register::<Counter>().await.unwrap();
+        assert_eq!(registry.channels().await, ["test:counter"]);
+        assert_eq!(registry.version(), 1);
+
+        assert!(registry.unregister("test:counter").await);
+        assert!(!registry.unregister("test:counter").await);
+        assert_eq!(registry.version(), 2);
+    }
+
+    #[tokio::test]
+    async fn dispatches_decoded_messages() {
+        let (connection, _client) = connection().await;
+        let event_bus = Arc::new(EventBus::default());
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        event_bus
+            .listen(false, {
+                let received = received.clone();
+                move |event: Arc<PluginMessageReceived<Counter>>| {
+                    received.lock().unwrap().push(event.message.0);
+                    async { None::<()> }
+                }
+            })
+            .await;
+
+        let registry = ChannelRegistry::default();
+        registry.register::<Counter>().await.unwrap();
+
+        let dispatch = |channel: &'static str, data: Vec<u8>| {
+            registry.dispatch(channel, data, connection.clone(), event_bus.clone())
+        };
+
+        assert!(dispatch("test:counter", Counter(300).encode()).await.unwrap());
+        assert!(!dispatch("test:unknown", Counter(1).encode()).await.unwrap());
+        assert_eq!(dispatch("test:counter", vec![0xFF]).await.unwrap_err().kind(), ErrorKind::InvalidData);
+
+        assert_eq!(*received.lock().unwrap(), [300]);
+    }
+}
@@ -0,0 +1,607 @@
+use rustmine_lib::{component::Style, dimension::DimensionType};
+use serde::Serialize;
+
+use crate::{
+    packet::{
+        clientbound::configuration::{RegistryDataEntry, RegistryDataPacket, RegistryTags, Tag, UpdateTagsPacket},
+        field,
+    },
+    RustmineServer,
+};
+
+//
+// The client can't enter Play without the data driven registries, so they're all sent during configuration.
+// Entries that also exist in minecraft:core only need their id when the client knows that pack, it fills in the rest.
+// The payloads below are only used by clients that don't, and are kept to what the client needs to load them.
+//
+
+/// Every Registry Data packet to send, in order.
+pub(crate) fn registry_data(server: &RustmineServer, knows_core: bool) -> Vec<RegistryDataPacket> {
+    vec![
+        dimension_types(server),
+        vanilla_registry("minecraft:worldgen/biome", biomes(server), knows_core),
+        vanilla_registry("minecraft:chat_type", chat_types(), knows_core),
+        vanilla_registry("minecraft:damage_type", damage_types(), knows_core),
+        vanilla_registry("minecraft:painting_variant", painting_variants(), knows_core),
+        vanilla_registry("minecraft:wolf_variant", wolf_variants(), knows_core),
+        vanilla_registry("minecraft:wolf_sound_variant", wolf_sound_variants(), knows_core),
+        vanilla_registry("minecraft:cat_variant", cat_variants(), knows_core),
+        vanilla_registry("minecraft:chicken_variant", chicken_variants(), knows_core),
+        vanilla_registry("minecraft:cow_variant", cow_variants(), knows_core),
+        vanilla_registry("minecraft:frog_variant", frog_variants(), knows_core),
+        vanilla_registry("minecraft:pig_variant", pig_variants(), knows_core),
+    ]
+}
+
+/// The server's tags, sent right after the registries they point into.
+pub(crate) fn update_tags(server: &RustmineServer) -> UpdateTagsPacket {
+    UpdateTagsPacket {
+        registries: server
+            .tag_registry
+            .entries()
+            .into_iter()
+            .map(|(registry, tags)| RegistryTags {
+                registry,
+                tags: tags
+                    .into_iter()
+                    .map(|(name, entries)| Tag { name, entries })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    field::write_nbt(value, &mut buffer);
+    buffer
+}
+
+fn vanilla_registry<T: Serialize>(
+    registry_id: &str,
+    entries: Vec<(String, T)>,
+    knows_core: bool,
+) -> RegistryDataPacket {
+    RegistryDataPacket {
+        registry_id: registry_id.to_string(),
+        entries: entries
+            .into_iter()
+            .map(|(id, value)| RegistryDataEntry {
+                id,
+                data: (!knows_core).then(|| encode(&value)),
+            })
+            .collect(),
+    }
+}
+
+//
+// Dimension types
+//
+
+// What the client reads, DimensionType keeps some of these as options that the client doesn't accept
+#[derive(Serialize)]
+struct NetworkDimensionType<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fixed_time: Option<i64>,
+    has_skylight: bool,
+    has_ceiling: bool,
+    ultrawarm: bool,
+    natural: bool,
+    coordinate_scale: f64,
+    bed_works: bool,
+    respawn_anchor_works: bool,
+    min_y: i32,
+    height: i32,
+    logical_height: i32,
+    infiniburn: &'a str,
+    effects: &'a str,
+    ambient_light: f32,
+    piglin_safe: bool,
+    has_raids: bool,
+    monster_spawn_light_level: i32,
+    monster_spawn_block_light_limit: i32,
+}
+
+impl<'a> From<&'a DimensionType> for NetworkDimensionType<'a> {
+    fn from(dim: &'a DimensionType) -> Self {
+        NetworkDimensionType {
+            fixed_time: dim.fixed_time.map(|time| time as i64),
+            has_skylight: dim.has_skylight,
+            has_ceiling: dim.has_ceiling,
+            ultrawarm: dim.ultrawarm,
+            natural: dim.natural,
+            coordinate_scale: dim.coordinate_scale,
+            bed_works: dim.bed_works,
+            respawn_anchor_works: dim.respawn_anchor_works,
+            min_y: dim.min_y,
+            height: dim.height,
+            logical_height: dim.logical_height,
+            infiniburn: &dim.infiniburn,
+            effects: &dim.effects,
+            ambient_light: dim.ambient_light,
+            piglin_safe: dim.piglin_safe,
+            has_raids: dim.has_raids,
+            monster_spawn_light_level: dim.monster_spawn_light_level.unwrap_or(0) as i32,
+            monster_spawn_block_light_limit: dim.monster_spawn_block_light_limit as i32,
+        }
+    }
+}
+
+// Always sent in full, the server decides what its dimensions look like even when they share a vanilla key
+fn dimension_types(server: &RustmineServer) -> RegistryDataPacket {
+    RegistryDataPacket {
+        registry_id: "minecraft:dimension_type".to_string(),
+        entries: server
+            .dimension_type_manager
+            .entries()
+            .into_iter()
+            .map(|(id, dim)| RegistryDataEntry {
+                data: Some(encode(&NetworkDimensionType::from(dim.as_ref()))),
+                id,
+            })
+            .collect(),
+    }
+}
+
+//
+// Biomes
+//
+
+#[derive(Serialize)]
+struct BiomeData {
+    has_precipitation: bool,
+    temperature: f32,
+    downfall: f32,
+    effects: BiomeEffects,
+}
+
+#[derive(Serialize)]
+struct BiomeEffects {
+    fog_color: i32,
+    sky_color: i32,
+    water_color: i32,
+    water_fog_color: i32,
+}
+
+// The generated biome data has no climate or colors yet, so every biome looks like plains without minecraft:core.
+// Sent in registry order since that's where the ids in chunk data come from.
+fn biomes(server: &RustmineServer) -> Vec<(String, BiomeData)> {
+    server
+        .biome_registry
+        .biomes()
+        .iter()
+        .map(|biome| {
+            (
+                biome.identifier().to_string(),
+                BiomeData {
+                    has_precipitation: true,
+                    temperature: 0.8,
+                    downfall: 0.4,
+                    effects: BiomeEffects {
+                        fog_color: 12638463,
+                        sky_color: 7907327,
+                        water_color: 4159204,
+                        water_fog_color: 329011,
+                    },
+                },
+            )
+        })
+        .collect()
+}
+
+//
+// Chat types
+//
+
+#[derive(Serialize)]
+struct ChatType {
+    chat: ChatTypeDecoration,
+    narration: ChatTypeDecoration,
+}
+
+#[derive(Serialize)]
+struct ChatTypeDecoration {
+    translation_key: &'static str,
+    parameters: &'static [&'static str],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    style: Option<Style>,
+}
+
+fn chat_types() -> Vec<(String, ChatType)> {
+    let decoration = |translation_key, parameters, style| ChatTypeDecoration {
+        translation_key,
+        parameters,
+        style,
+    };
+    let whisper = || Some(Style::new().color("gray").italic(true));
+    let narration = || decoration("chat.type.text.narrate", &["sender", "content"], None);
+
+    vec![
+        (
+            "chat",
+            decoration("chat.type.text", &["sender", "content"], None),
+            narration(),
+        ),
+        (
+            "emote_command",
+            decoration("chat.type.emote", &["sender", "content"], None),
+            decoration("chat.type.emote", &["sender", "content"], None),
+        ),
+        (
+            "msg_command_incoming",
+            decoration("commands.message.display.incoming", &["sender", "content"], whisper()),
+            narration(),
+        ),
+        (
+            "msg_command_outgoing",
+            decoration("commands.message.display.outgoing", &["target", "content"], whisper()),
+            narration(),
+        ),
+        (
+            "say_command",
+            decoration("chat.type.announcement", &["sender", "content"], None),
+            narration(),
+        ),
+        (
+            "team_msg_command_incoming",
+            decoration("chat.type.team.text", &["target", "sender", "content"], None),
+            narration(),
+        ),
+        (
+            "team_msg_command_outgoing",
+            decoration("chat.type.team.sent", &["target", "sender", "content"], None),
+            narration(),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, chat, narration)| (format!("minecraft:{}", name), ChatType { chat, narration }))
+    .collect()
+}
+
+//
+// Damage types, the client looks every one of these up by key so none can be left out
+//
+
+#[derive(Serialize)]
+struct DamageType {
+    message_id: &'static str,
+    scaling: &'static str,
+    exhaustion: f32,
+}
+
+fn damage_types() -> Vec<(String, DamageType)> {
+    const TYPES: &[(&str, &str)] = &[
+        ("arrow", "arrow"),
+        ("bad_respawn_point", "badRespawnPoint"),
+        ("cactus", "cactus"),
+        ("campfire", "inFire"),
+        ("cramming", "cramming"),
+        ("dragon_breath", "dragonBreath"),
+        ("drown", "drown"),
+        ("dry_out", "dryout"),
+        ("ender_pearl", "fall"),
+        ("explosion", "explosion"),
+        ("fall", "fall"),
+        ("falling_anvil", "anvil"),
+        ("falling_block", "fallingBlock"),
+        ("falling_stalactite", "fallingStalactite"),
+        ("fireball", "fireball"),
+        ("fireworks", "fireworks"),
+        ("fly_into_wall", "flyIntoWall"),
+        ("freeze", "freeze"),
+        ("generic", "generic"),
+        ("generic_kill", "genericKill"),
+        ("hot_floor", "hotFloor"),
+        ("in_fire", "inFire"),
+        ("in_wall", "inWall"),
+        ("indirect_magic", "indirectMagic"),
+        ("lava", "lava"),
+        ("lightning_bolt", "lightningBolt"),
+        ("mace_smash", "mace_smash"),
+        ("magic", "magic"),
+        ("mob_attack", "mob"),
+        ("mob_attack_no_aggro", "mob"),
+        ("mob_projectile", "mob"),
+        ("on_fire", "onFire"),
+        ("out_of_world", "outOfWorld"),
+        ("outside_border", "outsideBorder"),
+        ("player_attack", "player"),
+        ("player_explosion", "explosion.player"),
+        ("sonic_boom", "sonic_boom"),
+        ("spit", "mob"),
+        ("stalagmite", "stalagmite"),
+        ("starve", "starve"),
+        ("sting", "sting"),
+        ("sweet_berry_bush", "sweetBerryBush"),
+        ("thorns", "thorns"),
+        ("thrown", "thrown"),
+        ("trident", "trident"),
+        ("unattributed_fireball", "onFire"),
+        ("wind_charge", "mob"),
+        ("wither", "wither"),
+        ("wither_skull", "witherSkull"),
+    ];
+
+    TYPES
+        .iter()
+        .map(|(name, message_id)| {
+            let scaling = match *name {
+                "bad_respawn_point" | "explosion" | "player_explosion" => "always",
+                _ => "when_caused_by_living_non_player",
+            };
+
+            (
+                format!("minecraft:{}", name),
+                DamageType {
+                    message_id,
+                    scaling,
+                    exhaustion: 0.1,
+                },
+            )
+        })
+        .collect()
+}
+
+//
+// Paintings and mob variants, only the parts the client uses to render them
+//
+
+#[derive(Serialize)]
+struct PaintingVariant {
+    asset_id: String,
+    width: i32,
+    height: i32,
+}
+
+fn painting_variants() -> Vec<(String, PaintingVariant)> {
+    ["alban", "aztec", "aztec2", "bomb", "kebab", "plant", "wasteland"]
+        .into_iter()
+        .map(|name| {
+            (
+                format!("minecraft:{}", name),
+                PaintingVariant {
+                    asset_id: format!("minecraft:{}", name),
+                    width: 1,
+                    height: 1,
+                },
+            )
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+struct WolfVariant {
+    assets: WolfAssets,
+}
+
+#[derive(Serialize)]
+struct WolfAssets {
+    wild: String,
+    tame: String,
+    angry: String,
+}
+
+fn wolf_variants() -> Vec<(String, WolfVariant)> {
+    ["ashen", "black", "chestnut", "pale", "rusty", "snowy", "spotted", "striped", "woods"]
+        .into_iter()
+        .map(|name| {
+            // Pale was the only wolf before variants, so its textures kept the old name
+            let texture = match name {
+                "pale" => "minecraft:entity/wolf/wolf".to_string(),
+                _ => format!("minecraft:entity/wolf/wolf_{}", name),
+            };
+
+            (
+                format!("minecraft:{}", name),
+                WolfVariant {
+                    assets: WolfAssets {
+                        wild: texture.clone(),
+                        tame: format!("{}_tame", texture),
+                        angry: format!("{}_angry", texture),
+                    },
+                },
+            )
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+struct WolfSoundVariant {
+    ambient_sound: String,
+    death_sound: String,
+    growl_sound: String,
+    hurt_sound: String,
+    pant_sound: String,
+    whine_sound: String,
+}
+
+fn wolf_sound_variants() -> Vec<(String, WolfSoundVariant)> {
+    ["angry", "big", "classic", "cute", "grumpy", "puglin", "sad"]
+        .into_iter()
+        .map(|name| {
+            let prefix = match name {
+                "classic" => "minecraft:entity.wolf".to_string(),
+                _ => format!("minecraft:entity.wolf_{}", name),
+            };
+
+            (
+                format!("minecraft:{}", name),
+                WolfSoundVariant {
+                    ambient_sound: format!("{}.ambient", prefix),
+                    death_sound: format!("{}.death", prefix),
+                    growl_sound: format!("{}.growl", prefix),
+                    hurt_sound: format!("{}.hurt", prefix),
+                    pant_sound: format!("{}.pant", prefix),
+                    whine_sound: format!("{}.whine", prefix),
+                },
+            )
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+struct MobVariant {
+    asset_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<&'static str>,
+}
+
+fn mob_variants(
+    mob: &str,
+    variants: &[(&str, &str, Option<&'static str>)],
+) -> Vec<(String, MobVariant)> {
+    variants
+        .iter()
+        .map(|(name, texture, model)| {
+            (
+                format!("minecraft:{}", name),
+                MobVariant {
+                    asset_id: format!("minecraft:entity/{}/{}", mob, texture),
+                    model: *model,
+                },
+            )
+        })
+        .collect()
+}
+
+fn cat_variants() -> Vec<(String, MobVariant)> {
+    mob_variants(
+        "cat",
+        &[
+            ("all_black", "all_black", None),
+            ("black", "black", None),
+            ("british_shorthair", "british_shorthair", None),
+            ("calico", "calico", None),
+            ("jellie", "jellie", None),
+            ("persian", "persian", None),
+            ("ragdoll", "ragdoll", None),
+            ("red", "red", None),
+            ("siamese", "siamese", None),
+            ("tabby", "tabby", None),
+            ("white", "white", None),
+        ],
+    )
+}
+
+fn chicken_variants() -> Vec<(String, MobVariant)> {
+    mob_variants(
+        "chicken",
+        &[
+            ("cold", "cold_chicken", Some("cold")),
+            ("temperate", "temperate_chicken", Some("normal")),
+            ("warm", "warm_chicken", Some("normal")),
+        ],
+    )
+}
+
+fn cow_variants() -> Vec<(String, MobVariant)> {
+    mob_variants(
+        "cow",
+        &[
+            ("cold", "cold_cow", Some("cold")),
+            ("temperate", "temperate_cow", Some("normal")),
+            ("warm", "warm_cow", Some("warm")),
+        ],
+    )
+}
+
+fn frog_variants() -> Vec<(String, MobVariant)> {
+    mob_variants(
+        "frog",
+        &[
+            ("cold", "cold_frog", None),
+            ("temperate", "temperate_frog", None),
+            ("warm", "warm_frog", None),
+        ],
+    )
+}
+
+fn pig_variants() -> Vec<(String, MobVariant)> {
+    mob_variants(
+        "pig",
+        &[
+            ("cold", "cold_pig", Some("cold")),
+            ("temperate", "temperate_pig", Some("normal")),
+            ("warm", "warm_pig", Some("normal")),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+
+    fn registry_ids(packets: &[RegistryDataPacket]) -> Vec<&str> {
+        packets.iter().map(|packet| packet.registry_id.as_str()).collect()
+    }
+
+    fn entry_ids(packet: &RegistryDataPacket) -> Vec<&str> {
+        packet.entries.iter().map(|entry| entry.id.as_str()).collect()
+    }
+
+    #[test]
+    fn sends_every_registry_the_client_needs() {
+        let server = RustmineServer::new(ServerConfig::default());
+        let server = server.try_lock().unwrap();
+
+        for knows_core in [false, true] {
+            assert_eq!(
+                registry_ids(&registry_data(&server, knows_core)),
+                [
+                    "minecraft:dimension_type",
+                    "minecraft:worldgen/biome",
+                    "minecraft:chat_type",
+                    "minecraft:damage_type",
+                    "minecraft:painting_variant",
+                    "minecraft:wolf_variant",
+                    "minecraft:wolf_sound_variant",
+                    "minecraft:cat_variant",
+                    "minecraft:chicken_variant",
+                    "minecraft:cow_variant",
+                    "minecraft:frog_variant",
+                    "minecraft:pig_variant",
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_the_entries_in_order() {
+        let server = RustmineServer::new(ServerConfig::default());
+        let server = server.try_lock().unwrap();
+        let packets = registry_data(&server, false);
+
+        // Chunks refer to biomes by their index in the registry
+        let biomes: Vec<&str> = server.biome_registry.biomes().iter().map(|biome| biome.identifier()).collect();
+        assert!(!biomes.is_empty());
+        assert_eq!(entry_ids(&packets[1]), biomes);
+
+        let dimensions: Vec<String> =
+            server.dimension_type_manager.entries().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(entry_ids(&packets[0]), dimensions);
+
+        // Either way the client gets the same entries, in the same order
+        let known = registry_data(&server, true);
+        for (packet, known) in packets.iter().zip(&known) {
+            assert_eq!(entry_ids(packet), entry_ids(known));
+        }
+    }
+
+    #[test]
+    fn leaves_the_data_out_when_the_client_knows_core() {
+        let server = RustmineServer::new(ServerConfig::default());
+        let server = server.try_lock().unwrap();
+
+        for packet in registry_data(&server, false) {
+            assert!(packet.entries.iter().all(|entry| entry.data.is_some()), "{}", packet.registry_id);
+        }
+
+        // Except for dimension types, which the server always decides on
+        let packets = registry_data(&server, true);
+        assert!(packets[0].entries.iter().all(|entry| entry.data.is_some()));
+        for packet in &packets[1..] {
+            assert!(packet.entries.iter().all(|entry| entry.data.is_none()), "{}", packet.registry_id);
+        }
+    }
+}
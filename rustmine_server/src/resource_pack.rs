@@ -0,0 +1,130 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    io::{Error, ErrorKind},
+};
+
+use rustmine_lib::data;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::packet::{
+    field::VarIntField,
+    serverbound::{configuration::ConfigurationResourcePackResponsePacket, play::PlayResourcePackResponsePacket},
+};
+
+pub const MAX_URL_LENGTH: usize = 32767;
+pub const HASH_LENGTH: usize = 40; // SHA-1 as hex
+
+//
+// Every pack pushed to a client is tracked by its id until the client is done with it. The client reports a few
+// steps along the way (accepted, downloaded) before a final answer, which is what wait_for resolves with.
+//
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResourcePackResult {
+    Loaded,
+    Declined,
+    FailedDownload,
+    Accepted,
+    Downloaded,
+    InvalidUrl,
+    FailedReload,
+    Discarded,
+}
+
+impl ResourcePackResult {
+    /// Whether the client is done with the pack, everything but accepted and downloaded.
+    pub fn is_final(&self) -> bool {
+        !matches!(self, ResourcePackResult::Accepted | ResourcePackResult::Downloaded)
+    }
+}
+
+impl VarIntField for ResourcePackResult {
+    fn read_varint(buffer: &[u8], position: &mut usize) -> Result<Self, Error> {
+        match data::read_varint(buffer, position)? {
+            0 => Ok(ResourcePackResult::Loaded),
+            1 => Ok(ResourcePackResult::Declined),
+            2 => Ok(ResourcePackResult::FailedDownload),
+            3 => Ok(ResourcePackResult::Accepted),
+            4 => Ok(ResourcePackResult::Downloaded),
+            5 => Ok(ResourcePackResult::InvalidUrl),
+            6 => Ok(ResourcePackResult::FailedReload),
+            7 => Ok(ResourcePackResult::Discarded),
+            id => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid resource pack result: {}", id),
+            )),
+        }
+    }
+
+    fn write_varint(&self, buffer: &mut Vec<u8>) {
+        data::write_varint(buffer, *self as u32);
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ResourcePacks {
+    statuses: HashMap<Uuid, Option<ResourcePackResult>>, // None until the client answers
+    waiting: HashMap<Uuid, Vec<oneshot::Sender<ResourcePackResult>>>,
+}
+
+impl ResourcePacks {
+    pub(crate) fn pushed(&mut self, id: Uuid) {
+        self.statuses.insert(id, None);
+    }
+
+    pub(crate) fn status(&self, id: &Uuid) -> Option<ResourcePackResult> {
+        self.statuses.get(id).copied().flatten()
+    }
+
+    // Resolves straight away if the client already gave its final answer
+    pub(crate) fn wait_for(&mut self, id: Uuid) -> oneshot::Receiver<ResourcePackResult> {
+        let (sender, receiver) = oneshot::channel();
+        match self.status(&id) {
+            Some(result) if result.is_final() => {
+                let _ = sender.send(result);
+            }
+            _ => self.waiting.entry(id).or_default().push(sender),
+        }
+        receiver
+    }
+
+    pub(crate) fn update(&mut self, id: Uuid, result: ResourcePackResult) {
+        self.statuses.insert(id, Some(result));
+
+        if result.is_final() {
+            for sender in self.waiting.remove(&id).into_iter().flatten() {
+                let _ = sender.send(result);
+            }
+        }
+    }
+}
+
+// The id and result of a Resource Pack Response, whichever state it was sent in
+pub(crate) fn resource_pack_response(packet: &dyn Any) -> Option<(Uuid, ResourcePackResult)> {
+    if let Some(p) = packet.downcast_ref::<ConfigurationResourcePackResponsePacket>() {
+        return Some((p.id, p.result));
+    }
+    packet
+        .downcast_ref::<PlayResourcePackResponsePacket>()
+        .map(|p| (p.id, p.result))
+}
+
+pub(crate) fn validate(url: &str, hash: &str) -> Result<(), Error> {
+    if url.len() > MAX_URL_LENGTH {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Resource pack URLs can't be longer than {} characters", MAX_URL_LENGTH),
+        ));
+    }
+
+    if !hash.is_empty() && (hash.len() != HASH_LENGTH || !hash.chars().all(|c| c.is_ascii_hexdigit())) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Resource pack hashes must be {} hex characters", HASH_LENGTH),
+        ));
+    }
+
+    Ok(())
+}
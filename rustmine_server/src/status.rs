@@ -0,0 +1,145 @@
+use std::{io::{Error, ErrorKind}, path::Path};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use rand::seq::SliceRandom;
+use uuid::Uuid;
+
+use crate::packet::clientbound::status::StatusPlayerEntry;
+
+pub const MAX_SAMPLE_PLAYERS: usize = 12; // Same as vanilla
+pub const FAVICON_SIZE: u32 = 64;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+#[derive(Clone, Debug)]
+pub struct OnlinePlayer {
+    pub username: String,
+    pub uuid: Uuid,
+    pub listed: bool, // The client's "Allow Server Listings" option
+}
+
+/// Up to [`MAX_SAMPLE_PLAYERS`] players picked at random, the ones that opted out of listings are anonymized like vanilla does.
+pub fn sample_players(players: &[OnlinePlayer]) -> Vec<StatusPlayerEntry> {
+    players
+        .choose_multiple(&mut rand::thread_rng(), MAX_SAMPLE_PLAYERS)
+        .map(|player| match player.listed {
+            true => StatusPlayerEntry {
+                name: player.username.clone(),
+                id: player.uuid.hyphenated().to_string(),
+            },
+            false => StatusPlayerEntry {
+                name: "Anonymous Player".to_string(),
+                id: Uuid::nil().hyphenated().to_string(),
+            },
+        })
+        .collect()
+}
+
+/// Reads a 64x64 PNG into the data URI the client expects in the status response.
+pub fn load_favicon(path: impl AsRef<Path>) -> Result<String, Error> {
+    let png = std::fs::read(path)?;
+
+    // The signature, then the IHDR chunk which always comes first and starts with the width and height
+    if png.len() < 24 || png[..8] != PNG_SIGNATURE || &png[12..16] != b"IHDR" {
+        return Err(Error::new(ErrorKind::InvalidData, "Favicon is not a PNG"));
+    }
+
+    let width = u32::from_be_bytes([png[16], png[17], png[18], png[19]]);
+    let height = u32::from_be_bytes([png[20], png[21], png[22], png[23]]);
+    if width != FAVICON_SIZE || height != FAVICON_SIZE {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Favicon must be {}x{}, got {}x{}",
+                FAVICON_SIZE, FAVICON_SIZE, width, height
+            ),
+        ));
+    }
+
+    Ok(format!("data:image/png;base64,{}", BASE64_STANDARD.encode(png)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(index: usize, listed: bool) -> OnlinePlayer {
+        OnlinePlayer { username: format!("Player{}", index), uuid: Uuid::new_v4(), listed }
+    }
+
+    // Just the signature and IHDR, nothing past the size is looked at
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend(13u32.to_be_bytes());
+        png.extend(b"IHDR");
+        png.extend(width.to_be_bytes());
+        png.extend(height.to_be_bytes());
+        png.extend([8, 6, 0, 0, 0]);
+        png
+    }
+
+    fn write_favicon(name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("rustmine-{}-{}.png", name, Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn caps_the_sample() {
+        let players: Vec<OnlinePlayer> = (0..30).map(|index| player(index, true)).collect();
+        let sample = sample_players(&players);
+
+        assert_eq!(sample.len(), MAX_SAMPLE_PLAYERS);
+        for entry in &sample {
+            assert!(players.iter().any(|player| player.username == entry.name));
+        }
+        assert_eq!(sample_players(&players[..3]).len(), 3);
+    }
+
+    #[test]
+    fn anonymizes_hidden_players() {
+        let hidden = player(0, false);
+        let sample = sample_players(std::slice::from_ref(&hidden));
+
+        assert_eq!(sample[0].name, "Anonymous Player");
+        assert_eq!(sample[0].id, Uuid::nil().hyphenated().to_string());
+        assert!(sample.iter().all(|entry| entry.name != hidden.username));
+    }
+
+    #[test]
+    fn loads_a_valid_favicon() {
+        let contents = png(FAVICON_SIZE, FAVICON_SIZE);
+        let path = write_favicon("valid", &contents);
+        let favicon = load_favicon(&path);
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(favicon.unwrap(), format!("data:image/png;base64,{}", BASE64_STANDARD.encode(contents)));
+    }
+
+    #[test]
+    fn rejects_a_missing_favicon() {
+        let path = std::env::temp_dir().join(format!("rustmine-missing-{}.png", Uuid::new_v4()));
+        assert_eq!(load_favicon(path).unwrap_err().kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn rejects_favicons_of_the_wrong_size() {
+        for (width, height) in [(32, 32), (64, 128), (128, 64)] {
+            let path = write_favicon("size", &png(width, height));
+            let error = load_favicon(&path).unwrap_err();
+            std::fs::remove_file(path).unwrap();
+
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+            assert!(error.to_string().contains(&format!("got {}x{}", width, height)));
+        }
+    }
+
+    #[test]
+    fn rejects_favicons_that_are_not_pngs() {
+        let path = write_favicon("not-png", b"GIF89a, definitely not a PNG");
+        let error = load_favicon(&path).unwrap_err();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(error.to_string(), "Favicon is not a PNG");
+    }
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use rustmine_lib::{
-    chunk::ChunkGenerator,
+    chunk::{Chunk, ChunkGenerator},
     dimension::{self, DimensionType},
 };
 
@@ -30,3 +30,13 @@ pub struct World {
     pub dimension_type: Arc<dimension::DimensionType>,
     pub generator: Arc<dyn ChunkGenerator>,
 }
+
+impl World {
+    /// A new chunk of this world with its biomes, then its blocks, filled in by the generator.
+    pub fn generate_chunk(&self, x: i32, z: i32) -> Arc<Chunk> {
+        let chunk = Arc::new(Chunk::new(x, z, &self.dimension_type));
+        self.generator.generate_biomes(chunk.clone());
+        self.generator.generate_chunk(chunk.clone());
+        chunk
+    }
+}
@@ -0,0 +1,58 @@
+// Shared by the integration tests, not every file uses all of it
+#![allow(dead_code)]
+
+use std::net::SocketAddr;
+
+use rustmine_lib::data;
+use rustmine_server::{config::ServerConfig, RustmineServer, Shared};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+// Port 0 lets the system pick a free one, read it back from the address start returns
+pub fn config(online_mode: bool) -> ServerConfig {
+    ServerConfig {
+        bind_address: "127.0.0.1".into(),
+        port: 0,
+        online_mode,
+        compression_threshold: None,
+        ..Default::default()
+    }
+}
+
+// Connections are queued from the moment the server is bound, so they can be made as soon as this returns
+pub async fn start(server: Shared<RustmineServer>) -> SocketAddr {
+    let listener = RustmineServer::bind(&server).await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(RustmineServer::serve(server, listener));
+    address
+}
+
+pub fn handshake(address: SocketAddr, next_state: u32) -> Vec<u8> {
+    let mut handshake = vec![0x00];
+    data::write_varint(&mut handshake, 771);
+    data::write_string(&mut handshake, "localhost");
+    data::write_ushort(&mut handshake, address.port());
+    data::write_varint(&mut handshake, next_state);
+    frame(handshake)
+}
+
+pub fn frame(body: Vec<u8>) -> Vec<u8> {
+    let mut framed = Vec::new();
+    data::write_varint(&mut framed, body.len() as u32);
+    framed.extend(body);
+    framed
+}
+
+pub async fn read_frame<S: AsyncRead + Unpin>(stream: &mut S) -> Option<Vec<u8>> {
+    let mut length = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = stream.read_u8().await.ok()?;
+        length |= ((byte & 0x7F) as u32) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+
+    let mut body = vec![0; length as usize];
+    stream.read_exact(&mut body).await.ok()?;
+    Some(body)
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use std::net::SocketAddr;
+
+use common::{config, frame, handshake, read_frame, start};
+use rustmine_lib::data;
+use rustmine_server::RustmineServer;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+// Everything the server sends back to `request` until it closes the connection
+async fn exchange(address: SocketAddr, request: &[u8]) -> Vec<u8> {
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    stream.write_all(request).await.unwrap();
+    stream.shutdown().await.unwrap();
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test]
+async fn answers_a_leading_0xfe_with_a_kick() {
+    let address = start(RustmineServer::new(config(false))).await;
+
+    for request in [&[0xFE][..], &[0xFE, 0x01], &[0xFE, 0x01, 0xFA]] {
+        let response = exchange(address, request).await;
+        assert_eq!(response[0], 0xFF);
+
+        let length = u16::from_be_bytes([response[1], response[2]]) as usize;
+        assert_eq!(response.len(), 3 + length * 2);
+    }
+}
+
+#[tokio::test]
+async fn lets_modern_handshakes_through() {
+    let address = start(RustmineServer::new(config(false))).await;
+
+    let mut request = handshake(address, 1);
+    request.extend(frame(vec![0x00]));
+
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    stream.write_all(&request).await.unwrap();
+
+    // A status response packet, not a kick
+    let body = read_frame(&mut stream).await.unwrap();
+
+    let mut position = 0;
+    assert_eq!(data::read_varint(&body, &mut position).unwrap(), 0x00);
+    let json = data::read_string(&body, &mut position).unwrap();
+    assert!(json.contains("\"protocol\":771"));
+}
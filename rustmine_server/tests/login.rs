@@ -0,0 +1,114 @@
+mod common;
+
+use std::{
+    net::IpAddr,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use common::{config, frame, handshake, read_frame, start};
+use rsa::{pkcs8::DecodePublicKey, Pkcs1v15Encrypt, RsaPublicKey};
+use rustmine_lib::{data, game_profile::GameProfile};
+use rustmine_server::{
+    auth::{server_hash, SessionVerifier, SessionVerifierResult},
+    packet::encryption::CipherStream,
+    RustmineServer,
+};
+use tokio::{io::AsyncWriteExt, net::TcpStream};
+use uuid::Uuid;
+
+const PROFILE_UUID: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+
+// Accepts or rejects everyone, remembering what it was asked
+struct MockVerifier {
+    accept: bool,
+    asked: Mutex<Option<(String, String)>>,
+}
+
+impl SessionVerifier for MockVerifier {
+    fn has_joined<'a>(
+        &'a self,
+        username: &'a str,
+        server_hash: &'a str,
+        _ip: Option<IpAddr>,
+    ) -> Pin<Box<dyn Future<Output = SessionVerifierResult> + Send + 'a>> {
+        Box::pin(async move {
+            *self.asked.lock().unwrap() = Some((username.to_string(), server_hash.to_string()));
+
+            Ok(self.accept.then(|| GameProfile {
+                username: "Notch".to_string(),
+                uuid: Uuid::parse_str(PROFILE_UUID).unwrap(),
+                properties: vec![],
+            }))
+        })
+    }
+}
+
+// Runs an online mode server with `verifier` and logs in as far as the first packet after encryption
+async fn log_in(verifier: Arc<MockVerifier>) -> Option<Vec<u8>> {
+    let server = RustmineServer::new(config(true));
+    server.lock().await.session_verifier = verifier.clone();
+    let address = start(server).await;
+
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    stream.write_all(&handshake(address, 2)).await.unwrap();
+
+    let mut login_start = vec![0x00];
+    data::write_string(&mut login_start, "Notch");
+    data::write_uuid(&mut login_start, &Uuid::nil());
+    stream.write_all(&frame(login_start)).await.unwrap();
+
+    // Encryption Request: server id, public key, verify token, should authenticate
+    let request = read_frame(&mut stream).await.unwrap();
+    assert_eq!(request[0], 0x01);
+    let mut position = 1;
+    assert_eq!(data::read_string(&request, &mut position).unwrap(), "");
+    let key_length = data::read_varint(&request, &mut position).unwrap() as usize;
+    let public_key_der = data::read_bytes(&request, &mut position, key_length).unwrap();
+    let token_length = data::read_varint(&request, &mut position).unwrap() as usize;
+    let verify_token = data::read_bytes(&request, &mut position, token_length).unwrap();
+    assert!(data::read_bool(&request, &mut position).unwrap());
+
+    let public_key = RsaPublicKey::from_public_key_der(&public_key_der).unwrap();
+    let shared_secret = [7u8; 16];
+    let mut rng = rand::thread_rng();
+
+    let mut response = vec![0x01];
+    let encrypted_secret = public_key.encrypt(&mut rng, Pkcs1v15Encrypt, &shared_secret).unwrap();
+    data::write_varint(&mut response, encrypted_secret.len() as u32);
+    response.extend(encrypted_secret);
+    let encrypted_token = public_key.encrypt(&mut rng, Pkcs1v15Encrypt, &verify_token).unwrap();
+    data::write_varint(&mut response, encrypted_token.len() as u32);
+    response.extend(encrypted_token);
+    stream.write_all(&frame(response)).await.unwrap();
+
+    let mut stream = CipherStream::new(stream);
+    stream.enable_encryption(&shared_secret).unwrap();
+    let reply = tokio::time::timeout(Duration::from_secs(5), read_frame(&mut stream)).await.unwrap();
+
+    let asked = verifier.asked.lock().unwrap().clone();
+    assert_eq!(asked, Some(("Notch".to_string(), server_hash("", &shared_secret, &public_key_der))));
+    reply
+}
+
+#[tokio::test]
+async fn accepted_session_logs_in_with_the_verified_profile() {
+    let verifier = Arc::new(MockVerifier { accept: true, asked: Mutex::new(None) });
+    let reply = log_in(verifier).await.unwrap();
+
+    // Login Success with the profile the verifier gave back
+    assert_eq!(reply[0], 0x02);
+    let mut position = 1;
+    assert_eq!(data::read_uuid(&reply, &mut position).unwrap().to_string(), PROFILE_UUID);
+    assert_eq!(data::read_string(&reply, &mut position).unwrap(), "Notch");
+}
+
+#[tokio::test]
+async fn rejected_session_is_disconnected() {
+    let verifier = Arc::new(MockVerifier { accept: false, asked: Mutex::new(None) });
+
+    // Login Disconnect instead of Login Success
+    let reply = log_in(verifier).await.unwrap();
+    assert_eq!(reply[0], 0x00);
+}